@@ -1,11 +1,107 @@
-use cd_core::{ObjectGuid, WorldPos};
-use cd_engine::{Engine, InputCmd};
-use cd_map::{Chunk, MaterialId, Tile, TileFlags};
+use cd_core::{GuidAllocator, ObjectKind, WorldPos};
+use cd_engine::{Engine, EngineState, InputCmd};
+use cd_map::{Chunk, Tile, TileFlags};
 use cd_net::{protocol::ServerPacket, protocol::EntityView};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::thread;
 use std::time::Duration;
 use tokio::sync::{broadcast, mpsc};
-use tracing::{info, Level};
+use tracing::{error, info, warn, Level};
+
+/// Seed мира, если не задан `CD_WORLD_SEED`.
+const DEFAULT_WORLD_SEED: u64 = 0xC0D5_EED0;
+
+/// Seed мира из окружения. Один и тот же seed нужен между перезапусками,
+/// иначе огонь, окружение и прочая случайность поведут себя иначе.
+fn world_seed() -> u64 {
+    match std::env::var("CD_WORLD_SEED") {
+        Ok(raw) => raw.trim().parse().unwrap_or_else(|_| {
+            warn!("CD_WORLD_SEED={:?} is not a u64, using default", raw);
+            DEFAULT_WORLD_SEED
+        }),
+        Err(_) => DEFAULT_WORLD_SEED,
+    }
+}
+
+/// Файл состояния аллокатора GUID, если не задан `CD_GUID_STATE`.
+const DEFAULT_GUID_STATE: &str = "data/guids.json";
+/// Как часто сбрасывать состояние аллокатора на диск (если оно менялось).
+///
+/// Ограничение: при падении теряются выдачи за последний интервал, и после
+/// перезапуска те же GUID'ы выдадутся снова. Сейчас это безопасно: здесь выдаются
+/// только GUID'ы игроков, они живут лишь в памяти процесса и при старте все
+/// освобождаются (`free_all`). Виды, чьи GUID'ы пишутся на диск (предметы в мире),
+/// должны сохранять аллокатор до того, как записать GUID куда-либо еще.
+const GUID_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Загрузить аллокатор GUID. Нет файла — первый запуск.
+/// Игроки прошлого процесса уже не в сети: их GUID'ы освобождаем, иначе индексы утекут.
+fn load_guids(path: &Path) -> GuidAllocator {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return GuidAllocator::new(1),
+        Err(e) => {
+            // Начать с нуля нельзя: выдадим GUID'ы, которые уже где-то записаны
+            error!("Failed to read GUID state {:?}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    match serde_json::from_str::<GuidAllocator>(&text) {
+        Ok(mut guids) => {
+            let freed = guids.free_all(ObjectKind::Player);
+            info!("Restored GUID state from {:?}, released {} stale player GUIDs", path, freed);
+            guids
+        }
+        Err(e) => {
+            error!("GUID state {:?} is corrupt: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Файл состояния движка (RNG и тик), если не задан `CD_ENGINE_STATE`.
+const DEFAULT_ENGINE_STATE: &str = "data/engine.json";
+/// Как часто (в тиках) сохранять состояние движка. 20 TPS — раз в секунду.
+const ENGINE_SAVE_EVERY: u64 = 20;
+
+/// Поднять движок. Есть сохранение — продолжаем с его RNG и тика,
+/// иначе новый мир с `seed`.
+fn load_engine(path: &Path, seed: u64) -> Engine {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Engine::with_seed(seed),
+        Err(e) => {
+            // Новый RNG повторит случайность, уже случившуюся в мире
+            error!("Failed to read engine state {:?}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    match serde_json::from_str::<EngineState>(&text) {
+        Ok(state) => {
+            if state.rng.seed() != seed {
+                warn!("Saved world seed {} overrides configured seed {}", state.rng.seed(), seed);
+            }
+            info!("Restored engine state from {:?} at tick {}", path, state.tick);
+            Engine::from_state(state)
+        }
+        Err(e) => {
+            error!("Engine state {:?} is corrupt: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Записать через временный файл, чтобы падение посреди записи не испортило состояние.
+fn save_json<T: Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_string(value)?)?;
+    std::fs::rename(tmp, path)
+}
 
 #[tokio::main]
 async fn main() {
@@ -24,19 +120,46 @@ async fn main() {
     let (snapshot_tx, _) = broadcast::channel::<ServerPacket>(16);
     let snapshot_tx_net = snapshot_tx.clone();
 
+    // GUID'ы игроков выдает сеть при логине.
+    // Состояние аллокатора переживает перезапуск, чтобы GUID'ы не повторялись.
+    let guid_path = std::env::var("CD_GUID_STATE").map_or_else(|_| PathBuf::from(DEFAULT_GUID_STATE), PathBuf::from);
+    let initial = load_guids(&guid_path);
+    if let Err(e) = save_json(&guid_path, &initial) {
+        error!("Failed to save GUID state {:?}: {}", guid_path, e);
+    }
+    let guids = Arc::new(Mutex::new(initial.clone()));
+
+    let guids_saver = guids.clone();
+    tokio::spawn(async move {
+        let mut saved = initial;
+        let mut interval = tokio::time::interval(GUID_SAVE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let current = guids_saver.lock().unwrap_or_else(PoisonError::into_inner).clone();
+            if current == saved {
+                continue;
+            }
+            match save_json(&guid_path, &current) {
+                Ok(()) => saved = current,
+                Err(e) => error!("Failed to save GUID state {:?}: {}", guid_path, e),
+            }
+        }
+    });
+
+    let seed = world_seed();
+    info!("World seed: {}", seed);
+    let engine_path = std::env::var("CD_ENGINE_STATE").map_or_else(|_| PathBuf::from(DEFAULT_ENGINE_STATE), PathBuf::from);
+
     // 2. Запускаем Движок в отдельном OS потоке (CPU Bound)
     thread::spawn(move || {
-        let mut engine = Engine::new();
+        let mut engine = load_engine(&engine_path, seed);
 
         // Setup Map (Test)
         let mut chunk = Chunk::new();
-        chunk.set(5, 5, Tile { material: MaterialId(1), flags: TileFlags::SOLID, variant: 0 });
-        engine.map.insert_chunk(0, 0, chunk);
+        chunk.set_tile(5, 5, Tile { material: 1, flags: TileFlags::SOLID, variant: 0 });
+        engine.map.put_chunk(WorldPos::new(0, 0, 0), chunk);
 
-        // Spawn Test Player (чтобы было кем управлять)
-        // В реальной жизни это должно происходить по команде Login
-        let player_guid = ObjectGuid::new(1, 1, 1, 4); // index 4 (по длине слова "test")
-        engine.spawn_player(player_guid, "NetPlayer".to_string(), WorldPos::new(0, 0, 0));
+        // Игроки появляются по команде Join после логина
 
         let tick_rate = Duration::from_millis(50); // 20 TPS
         let mut tick_counter = 0;
@@ -52,6 +175,11 @@ async fn main() {
 
             // B. Тик Симуляции
             engine.tick(inputs);
+            if engine.current_tick().is_multiple_of(ENGINE_SAVE_EVERY)
+                && let Err(e) = save_json(&engine_path, &engine.save_state())
+            {
+                error!("Failed to save engine state {:?}: {}", engine_path, e);
+            }
 
             // C. Генерация Снапшота (Mock)
             // В реальной системе тут будет engine.create_snapshot()
@@ -60,9 +188,9 @@ async fn main() {
             // Запрашиваем данные из ECS для рендера
             // Тут мы нарушаем изоляцию для демо, в проде это будет внутри engine.snapshot()
             for (id, (pos, render)) in engine.world.query::<(&cd_ecs::components::Position, &cd_ecs::components::Render)>().iter() {
-                // Тут нужен маппинг Entity -> Guid, но пока фейк
+                let Some(guid) = engine.guid_of(id) else { continue };
                 entities_view.push(EntityView {
-                    guid: "test-guid".to_string(), // TODO: use real guid
+                    guid: guid.to_string(),
                     x: pos.0.x(),
                     y: pos.0.y(),
                     glyph: render.glyph,
@@ -90,5 +218,5 @@ async fn main() {
     });
 
     // 3. Запускаем Сеть (IO Bound) в текущем потоке (Tokio Runtime)
-    cd_net::run_server(8080, cmd_tx, snapshot_tx_net).await;
+    cd_net::run_server(8080, cmd_tx, snapshot_tx_net, guids).await;
 }
\ No newline at end of file
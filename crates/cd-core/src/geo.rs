@@ -1,10 +1,13 @@
-use serde::{Deserialize, Serialize, Serializer, Deserializer};
+use serde::{de, Deserialize, Serialize, Serializer, Deserializer};
 use serde::ser::SerializeStruct;
 use std::fmt;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
 
 /// Упакованная координата (X, Y, Z).
 /// Layout: [ Z (12) | Y (26) | X (26) ]
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
 pub struct WorldPos(u64);
 
 // Константы размеров мира (дублируем логику из map для инкапсуляции)
@@ -31,6 +34,16 @@ impl WorldPos {
     const OFFSET_Y: i32 = 1 << (Self::BITS_Y - 1);
     const OFFSET_Z: i32 = 1 << (Self::BITS_Z - 1);
 
+    // Допустимые координаты (включительно)
+    pub const X_MIN: i32 = -Self::OFFSET_X;
+    pub const X_MAX: i32 = Self::OFFSET_X - 1;
+    pub const Y_MIN: i32 = -Self::OFFSET_Y;
+    pub const Y_MAX: i32 = Self::OFFSET_Y - 1;
+    pub const Z_MIN: i32 = -Self::OFFSET_Z;
+    pub const Z_MAX: i32 = Self::OFFSET_Z - 1;
+
+    /// Упаковка без проверок: координаты вне диапазона заворачиваются по модулю
+    /// (x = X_MAX + 1 станет X_MIN). Для данных извне — `try_new`.
     pub const fn new(x: i32, y: i32, z: i32) -> Self {
         let ux = (x + Self::OFFSET_X) as u64 & Self::MASK_X;
         let uy = (y + Self::OFFSET_Y) as u64 & Self::MASK_Y;
@@ -39,6 +52,64 @@ impl WorldPos {
         Self((uz << Self::SHIFT_Z) | (uy << Self::SHIFT_Y) | ux)
     }
 
+    /// Упаковка с проверкой диапазона.
+    pub const fn try_new(x: i32, y: i32, z: i32) -> Result<Self, PosError> {
+        if x < Self::X_MIN || x > Self::X_MAX || y < Self::Y_MIN || y > Self::Y_MAX || z < Self::Z_MIN || z > Self::Z_MAX {
+            return Err(PosError::OutOfRange { x: x as i64, y: y as i64, z: z as i64 });
+        }
+        Ok(Self::new(x, y, z))
+    }
+
+    /// То же, что `try_new`, но для уже посчитанных (возможно, переполненных) сумм.
+    fn try_from_wide(x: i64, y: i64, z: i64) -> Result<Self, PosError> {
+        let err = PosError::OutOfRange { x, y, z };
+        match (i32::try_from(x), i32::try_from(y), i32::try_from(z)) {
+            (Ok(x), Ok(y), Ok(z)) => Self::try_new(x, y, z).map_err(|_| err),
+            _ => Err(err),
+        }
+    }
+
+    // --- Arithmetic ---
+
+    pub fn checked_add(self, offset: Offset) -> Option<Self> {
+        let (x, y, z) = self.shifted_wide(offset, 1);
+        Self::try_from_wide(x, y, z).ok()
+    }
+
+    pub fn checked_sub(self, offset: Offset) -> Option<Self> {
+        let (x, y, z) = self.shifted_wide(offset, -1);
+        Self::try_from_wide(x, y, z).ok()
+    }
+
+    /// Сдвиг с прижатием к краю мира по каждой оси.
+    pub fn saturating_add(self, offset: Offset) -> Self {
+        Self::clamped_wide(self.shifted_wide(offset, 1))
+    }
+
+    pub fn saturating_sub(self, offset: Offset) -> Self {
+        Self::clamped_wide(self.shifted_wide(offset, -1))
+    }
+
+    /// Координаты после сдвига на `sign * offset`, в i64: без переполнений и без
+    /// отрицания Offset (-i32::MIN в i32 не помещается).
+    fn shifted_wide(self, offset: Offset, sign: i64) -> (i64, i64, i64) {
+        let (x, y, z) = self.xyz();
+        (
+            x as i64 + sign * offset.dx as i64,
+            y as i64 + sign * offset.dy as i64,
+            z as i64 + sign * offset.dz as i64,
+        )
+    }
+
+    fn clamped_wide((x, y, z): (i64, i64, i64)) -> Self {
+        let clamp = |v: i64, min: i32, max: i32| v.clamp(min as i64, max as i64) as i32;
+        Self::new(
+            clamp(x, Self::X_MIN, Self::X_MAX),
+            clamp(y, Self::Y_MIN, Self::Y_MAX),
+            clamp(z, Self::Z_MIN, Self::Z_MAX),
+        )
+    }
+
     // --- Accessors ---
 
     #[inline(always)]
@@ -80,10 +151,11 @@ impl WorldPos {
     }
 
     /// Получить ключ региона (координаты чанка / 32)
-    /// self здесь должен быть уже chunk_key
+    /// self здесь должен быть уже chunk_key.
+    /// Z сохраняется: у каждого уровня свои регионы.
     pub fn region_key(&self) -> WorldPos {
-        let (cx, cy, _) = self.xyz();
-        WorldPos::new(cx >> REGION_SHIFT, cy >> REGION_SHIFT, 0)
+        let (cx, cy, cz) = self.xyz();
+        WorldPos::new(cx >> REGION_SHIFT, cy >> REGION_SHIFT, cz)
     }
 
     /// Индекс шарда (для chunk_key)
@@ -93,6 +165,174 @@ impl WorldPos {
     }
 }
 
+/// Смещение между позициями (dx, dy, dz).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Offset {
+    pub dx: i32,
+    pub dy: i32,
+    pub dz: i32,
+}
+
+impl Offset {
+    pub const ZERO: Offset = Offset { dx: 0, dy: 0, dz: 0 };
+
+    pub const fn new(dx: i32, dy: i32, dz: i32) -> Self {
+        Self { dx, dy, dz }
+    }
+
+    pub fn checked_add(self, rhs: Offset) -> Option<Offset> {
+        Some(Offset::new(self.dx.checked_add(rhs.dx)?, self.dy.checked_add(rhs.dy)?, self.dz.checked_add(rhs.dz)?))
+    }
+
+    pub fn saturating_add(self, rhs: Offset) -> Offset {
+        Offset::new(self.dx.saturating_add(rhs.dx), self.dy.saturating_add(rhs.dy), self.dz.saturating_add(rhs.dz))
+    }
+
+    pub fn checked_neg(self) -> Option<Offset> {
+        Some(Offset::new(self.dx.checked_neg()?, self.dy.checked_neg()?, self.dz.checked_neg()?))
+    }
+}
+
+// Операторы Offset паникуют при переполнении i32 в любой сборке (как и WorldPos + Offset),
+// а не заворачиваются молча. Мягкие варианты — checked_/saturating_.
+impl Neg for Offset {
+    type Output = Offset;
+
+    fn neg(self) -> Offset {
+        match self.checked_neg() {
+            Some(offset) => offset,
+            None => panic!("Offset overflow: -{:?}", self),
+        }
+    }
+}
+
+impl Add for Offset {
+    type Output = Offset;
+
+    fn add(self, rhs: Offset) -> Offset {
+        match self.checked_add(rhs) {
+            Some(offset) => offset,
+            None => panic!("Offset overflow: {:?} + {:?}", self, rhs),
+        }
+    }
+}
+
+// Сложение с выходом за край мира — это баг вызывающего, как переполнение целых:
+// паникуем, а не заворачиваем на другой конец карты. Мягкие варианты — checked_/saturating_.
+impl Add<Offset> for WorldPos {
+    type Output = WorldPos;
+
+    fn add(self, offset: Offset) -> WorldPos {
+        match self.checked_add(offset) {
+            Some(pos) => pos,
+            None => panic!("WorldPos overflow: {:?} + {:?}", self, offset),
+        }
+    }
+}
+
+impl Sub<Offset> for WorldPos {
+    type Output = WorldPos;
+
+    fn sub(self, offset: Offset) -> WorldPos {
+        match self.checked_sub(offset) {
+            Some(pos) => pos,
+            None => panic!("WorldPos overflow: {:?} - {:?}", self, offset),
+        }
+    }
+}
+
+impl AddAssign<Offset> for WorldPos {
+    fn add_assign(&mut self, offset: Offset) {
+        *self = *self + offset;
+    }
+}
+
+impl SubAssign<Offset> for WorldPos {
+    fn sub_assign(&mut self, offset: Offset) {
+        *self = *self - offset;
+    }
+}
+
+/// Разность позиций: `a - b` — смещение, которое переводит `b` в `a`.
+impl Sub for WorldPos {
+    type Output = Offset;
+
+    fn sub(self, other: WorldPos) -> Offset {
+        Offset::new(self.x() - other.x(), self.y() - other.y(), self.z() - other.z())
+    }
+}
+
+/// Прямоугольная область мира [min, max] включительно по всем осям.
+/// Движок и сеть проверяют по ней позиции, пришедшие извне.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldBounds {
+    min: WorldPos,
+    max: WorldPos,
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl WorldBounds {
+    /// Весь представимый мир.
+    pub const ALL: WorldBounds = WorldBounds {
+        min: WorldPos::new(WorldPos::X_MIN, WorldPos::Y_MIN, WorldPos::Z_MIN),
+        max: WorldPos::new(WorldPos::X_MAX, WorldPos::Y_MAX, WorldPos::Z_MAX),
+    };
+
+    /// Углы можно передавать в любом порядке.
+    pub fn new(a: WorldPos, b: WorldPos) -> Self {
+        Self {
+            min: WorldPos::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            max: WorldPos::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    pub fn min(&self) -> WorldPos {
+        self.min
+    }
+
+    pub fn max(&self) -> WorldPos {
+        self.max
+    }
+
+    pub fn contains(&self, pos: WorldPos) -> bool {
+        (self.min.x()..=self.max.x()).contains(&pos.x())
+            && (self.min.y()..=self.max.y()).contains(&pos.y())
+            && (self.min.z()..=self.max.z()).contains(&pos.z())
+    }
+
+    /// Ok(pos), если позиция внутри, иначе `PosError::OutOfBounds`.
+    pub fn check(&self, pos: WorldPos) -> Result<WorldPos, PosError> {
+        if self.contains(pos) { Ok(pos) } else { Err(PosError::OutOfBounds(pos)) }
+    }
+
+    /// Сырые координаты (например, из пакета): сначала диапазон упаковки, затем границы.
+    pub fn validate(&self, x: i32, y: i32, z: i32) -> Result<WorldPos, PosError> {
+        self.check(WorldPos::try_new(x, y, z)?)
+    }
+
+    /// Ближайшая к `pos` точка внутри границ.
+    pub fn clamp(&self, pos: WorldPos) -> WorldPos {
+        WorldPos::new(
+            pos.x().clamp(self.min.x(), self.max.x()),
+            pos.y().clamp(self.min.y(), self.max.y()),
+            pos.z().clamp(self.min.z(), self.max.z()),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PosError {
+    #[error("coordinates ({x}, {y}, {z}) are out of the packable range")]
+    OutOfRange { x: i64, y: i64, z: i64 },
+    #[error("position {0:?} is outside the world bounds")]
+    OutOfBounds(WorldPos),
+}
+
 impl fmt::Debug for WorldPos {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "({}, {}, {})", self.x(), self.y(), self.z())
@@ -100,14 +340,19 @@ impl fmt::Debug for WorldPos {
 }
 
 // --- Serialization ---
+// Человекочитаемые форматы (JSON) получают объект {"x":1,"y":2,"z":0},
+// бинарные (bincode и т.п.) — упакованный u64: 8 байт вместо трех i32.
+// Массив [x, y, z] включается на поле: #[serde(with = "cd_core::geo::as_array")].
+// Чтение в человекочитаемом формате принимает любой из трех видов.
 
 impl Serialize for WorldPos {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        // Сериализуем как объект, чтобы в JSON было читаемо {"x":1,"y":2,"z":0}
-        // Для бинарных форматов можно было бы сериализовать self.0 напрямую
+        if !serializer.is_human_readable() {
+            return serializer.serialize_u64(self.0);
+        }
         let (x, y, z) = self.xyz();
         let mut state = serializer.serialize_struct("WorldPos", 3)?;
         state.serialize_field("x", &x)?;
@@ -122,11 +367,70 @@ impl<'de> Deserialize<'de> for WorldPos {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        struct PosDto { x: i32, y: i32, z: i32 }
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PosVisitor)
+        } else {
+            deserializer.deserialize_u64(PosVisitor)
+        }
+    }
+}
+
+struct PosVisitor;
+
+impl<'de> de::Visitor<'de> for PosVisitor {
+    type Value = WorldPos;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a position as {x, y, z}, [x, y, z] or packed u64")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<WorldPos, E> {
+        // Раскладка занимает все 64 бита: любое число — валидная позиция
+        Ok(WorldPos(v))
+    }
 
-        let dto = PosDto::deserialize(deserializer)?;
-        Ok(WorldPos::new(dto.x, dto.y, dto.z))
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<WorldPos, A::Error> {
+        let mut next = |i| seq.next_element::<i32>()?.ok_or_else(|| de::Error::invalid_length(i, &self));
+        let (x, y, z) = (next(0)?, next(1)?, next(2)?);
+        WorldPos::try_new(x, y, z).map_err(de::Error::custom)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<WorldPos, A::Error> {
+        let (mut x, mut y, mut z) = (None, None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            let slot = match key.as_str() {
+                "x" => &mut x,
+                "y" => &mut y,
+                "z" => &mut z,
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                    continue;
+                }
+            };
+            *slot = Some(map.next_value::<i32>()?);
+        }
+        let x = x.ok_or_else(|| de::Error::missing_field("x"))?;
+        let y = y.ok_or_else(|| de::Error::missing_field("y"))?;
+        let z = z.ok_or_else(|| de::Error::missing_field("z"))?;
+        WorldPos::try_new(x, y, z).map_err(de::Error::custom)
+    }
+}
+
+/// Позиция массивом `[x, y, z]` — компактнее объекта в JSON (снапшоты, сейвы).
+/// В бинарных форматах — кортеж из трех i32.
+pub mod as_array {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(pos: &WorldPos, serializer: S) -> Result<S::Ok, S::Error> {
+        pos.xyz().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<WorldPos, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PosVisitor)
+        } else {
+            deserializer.deserialize_tuple(3, PosVisitor)
+        }
     }
 }
 
@@ -180,6 +484,100 @@ mod tests {
         assert_eq!(lx_neg, 15);
     }
 
+    #[test]
+    fn test_try_new_rejects_wrapping() {
+        assert!(WorldPos::try_new(WorldPos::X_MAX, WorldPos::Y_MIN, WorldPos::Z_MAX).is_ok());
+        assert!(WorldPos::try_new(WorldPos::X_MAX + 1, 0, 0).is_err());
+        assert!(WorldPos::try_new(0, WorldPos::Y_MIN - 1, 0).is_err());
+        assert!(WorldPos::try_new(0, 0, 2048).is_err());
+        assert!(WorldPos::try_new(i32::MAX, i32::MIN, 0).is_err());
+
+        // new() молча заворачивает — именно это и ловит try_new
+        assert_eq!(WorldPos::new(WorldPos::X_MAX + 1, 0, 0).x(), WorldPos::X_MIN);
+    }
+
+    #[test]
+    fn test_offset_arithmetic() {
+        let a = WorldPos::new(10, -5, 1);
+        let d = Offset::new(3, 4, -1);
+        assert_eq!(a + d, WorldPos::new(13, -1, 0));
+        assert_eq!(a + d - d, a);
+        assert_eq!((a + d) - a, d);
+
+        let edge = WorldPos::new(WorldPos::X_MAX, 0, 0);
+        assert_eq!(edge.checked_add(Offset::new(1, 0, 0)), None);
+        assert_eq!(edge.saturating_add(Offset::new(5, -3, 0)), WorldPos::new(WorldPos::X_MAX, -3, 0));
+        assert_eq!(edge.saturating_add(Offset::new(i32::MAX, 0, i32::MIN)), WorldPos::new(WorldPos::X_MAX, 0, WorldPos::Z_MIN));
+
+        // Вычитание i32::MIN: это сдвиг вправо на 2^31, а не влево
+        let min = Offset::new(i32::MIN, 0, i32::MIN);
+        assert_eq!(edge.checked_sub(min), None);
+        assert_eq!(WorldPos::new(0, 0, 0).saturating_sub(min), WorldPos::new(WorldPos::X_MAX, 0, WorldPos::Z_MAX));
+        assert_eq!(min.checked_neg(), None);
+
+        // Offset + Offset
+        let big = Offset::new(i32::MAX, 1, -1);
+        assert_eq!(big.checked_add(Offset::new(1, 0, 0)), None);
+        assert_eq!(big.saturating_add(Offset::new(1, 2, i32::MIN)), Offset::new(i32::MAX, 3, i32::MIN));
+        assert_eq!(d + d, Offset::new(6, 8, -2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Offset overflow")]
+    fn test_offset_add_overflow_panics() {
+        let _ = Offset::new(i32::MAX, 0, 0) + Offset::new(1, 0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "WorldPos overflow")]
+    fn test_add_overflow_panics() {
+        let _ = WorldPos::new(0, WorldPos::Y_MAX, 0) + Offset::new(0, 1, 0);
+    }
+
+    #[test]
+    fn test_world_bounds() {
+        let bounds = WorldBounds::new(WorldPos::new(100, 100, 0), WorldPos::new(-100, -100, -3));
+        assert_eq!(bounds.min(), WorldPos::new(-100, -100, -3));
+        assert!(bounds.contains(WorldPos::new(0, 100, -3)));
+        assert!(!bounds.contains(WorldPos::new(0, 101, 0)));
+        assert_eq!(bounds.check(WorldPos::new(0, 0, 1)), Err(PosError::OutOfBounds(WorldPos::new(0, 0, 1))));
+        assert!(matches!(bounds.validate(1 << 30, 0, 0), Err(PosError::OutOfRange { .. })));
+        assert_eq!(bounds.validate(5, 5, 0), Ok(WorldPos::new(5, 5, 0)));
+        assert_eq!(bounds.clamp(WorldPos::new(500, -7, 9)), WorldPos::new(100, -7, 0));
+
+        assert!(WorldBounds::ALL.contains(WorldPos::new(WorldPos::X_MIN, WorldPos::Y_MAX, WorldPos::Z_MIN)));
+    }
+
+    #[test]
+    fn test_serde_forms() {
+        let p = WorldPos::new(-100, 500, -5);
+
+        // JSON: читаемый объект; читаются также массив и u64
+        let json = serde_json::to_string(&p).unwrap();
+        assert_eq!(json, r#"{"x":-100,"y":500,"z":-5}"#);
+        assert_eq!(serde_json::from_str::<WorldPos>(&json).unwrap(), p);
+        assert_eq!(serde_json::from_str::<WorldPos>("[-100,500,-5]").unwrap(), p);
+        assert_eq!(serde_json::from_str::<WorldPos>(&p.0.to_string()).unwrap(), p);
+        assert!(serde_json::from_str::<WorldPos>("[1,2]").is_err());
+        assert!(serde_json::from_str::<WorldPos>(r#"{"x":1,"y":2,"z":4096}"#).is_err());
+
+        // Бинарный формат: ровно упакованный u64
+        let bin = bincode::serialize(&p).unwrap();
+        assert_eq!(bin.len(), 8);
+        assert_eq!(bincode::deserialize::<WorldPos>(&bin).unwrap(), p);
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct View {
+            #[serde(with = "as_array")]
+            pos: WorldPos,
+        }
+        let view = View { pos: p };
+        let json = serde_json::to_string(&view).unwrap();
+        assert_eq!(json, r#"{"pos":[-100,500,-5]}"#);
+        assert_eq!(serde_json::from_str::<View>(&json).unwrap(), view);
+        assert_eq!(bincode::deserialize::<View>(&bincode::serialize(&view).unwrap()).unwrap(), view);
+    }
+
     #[test]
     fn test_region_key() {
         // Чанк 33 находится в регионе 1 (32 чанка на регион)
@@ -187,5 +585,23 @@ mod tests {
         let reg_key = chunk_pos.region_key();
         assert_eq!(reg_key.x(), 1);
         assert_eq!(reg_key.y(), 0);
+
+        // Уровни не делят регионы
+        let lower = WorldPos::new(33, 0, -1).region_key();
+        assert_eq!(lower.z(), -1);
+        assert_ne!(lower, reg_key);
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_pos_archive_roundtrip() {
+        for (x, y, z) in [(0, 0, 0), (-1, -1, -1), (123_456, -654_321, WorldPos::Z_MIN), (-5, 7, WorldPos::Z_MAX)] {
+            let pos = WorldPos::new(x, y, z);
+            let bytes = rkyv::to_bytes::<_, 64>(&pos).unwrap();
+            let archived = rkyv::check_archived_root::<WorldPos>(&bytes).unwrap();
+            let restored: WorldPos = rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible).unwrap();
+            assert_eq!(restored, pos);
+            assert_eq!((restored.x(), restored.y(), restored.z()), (x, y, z));
+        }
     }
-}
\ No newline at end of file
+}
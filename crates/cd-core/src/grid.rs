@@ -1,9 +1,13 @@
-use crate::geo::WorldPos;
+use crate::geo::{Offset, WorldPos};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Направления движения.
 /// В Go: enums/tiles.go
+/// В протоколе передаются стабильными строками ("north", "north_east", "up"), см. `as_str`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 #[repr(u8)]
 pub enum Direction {
     None = 0,
@@ -38,11 +42,111 @@ impl Direction {
         }
     }
 
+    /// Направление по дельте (берется только знак, длина не важна).
+    /// Вертикаль вместе с горизонталью не выражается одним направлением — вернет `None`.
+    pub fn from_delta(dx: i32, dy: i32, dz: i32) -> Direction {
+        match (dx.signum(), dy.signum(), dz.signum()) {
+            (0, 0, 1) => Self::Up,
+            (0, 0, -1) => Self::Down,
+            (_, _, 0) => match (dx.signum(), dy.signum()) {
+                (0, -1) => Self::North,
+                (0, 1) => Self::South,
+                (-1, 0) => Self::West,
+                (1, 0) => Self::East,
+                (-1, -1) => Self::NorthWest,
+                (1, -1) => Self::NorthEast,
+                (-1, 1) => Self::SouthWest,
+                (1, 1) => Self::SouthEast,
+                _ => Self::None,
+            },
+            _ => Self::None,
+        }
+    }
+
+    /// Направление из `from` в сторону `to`.
+    pub fn from_positions(from: WorldPos, to: WorldPos) -> Direction {
+        Self::from_delta(to.x() - from.x(), to.y() - from.y(), to.z() - from.z())
+    }
+
+    /// Поворот на 45° по часовой стрелке. Вертикальные направления не меняются.
+    pub fn rotate_cw(&self) -> Direction {
+        match self {
+            Self::North => Self::NorthEast,
+            Self::NorthEast => Self::East,
+            Self::East => Self::SouthEast,
+            Self::SouthEast => Self::South,
+            Self::South => Self::SouthWest,
+            Self::SouthWest => Self::West,
+            Self::West => Self::NorthWest,
+            Self::NorthWest => Self::North,
+            other => *other,
+        }
+    }
+
+    /// Поворот на 45° против часовой стрелки.
+    pub fn rotate_ccw(&self) -> Direction {
+        match self {
+            Self::North => Self::NorthWest,
+            Self::NorthWest => Self::West,
+            Self::West => Self::SouthWest,
+            Self::SouthWest => Self::South,
+            Self::South => Self::SouthEast,
+            Self::SouthEast => Self::East,
+            Self::East => Self::NorthEast,
+            Self::NorthEast => Self::North,
+            other => *other,
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        let (dx, dy, dz) = self.offset();
+        Self::from_delta(-dx, -dy, -dz)
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        let (dx, dy, _) = self.offset();
+        dx != 0 && dy != 0
+    }
+
+    /// Ортогональные составляющие диагонали: NorthEast -> (North, East).
+    /// Для остальных направлений — `None`.
+    pub fn components(&self) -> Option<(Direction, Direction)> {
+        if !self.is_diagonal() {
+            return None;
+        }
+        let (dx, dy, _) = self.offset();
+        Some((Self::from_delta(0, dy, 0), Self::from_delta(dx, 0, 0)))
+    }
+
+    /// Стабильное имя для протокола (совпадает с serde).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::North => "north",
+            Self::South => "south",
+            Self::West => "west",
+            Self::East => "east",
+            Self::NorthWest => "north_west",
+            Self::NorthEast => "north_east",
+            Self::SouthWest => "south_west",
+            Self::SouthEast => "south_east",
+            Self::Up => "up",
+            Self::Down => "down",
+        }
+    }
+
     /// Список ортогональных соседей (4-way).
     pub const ORTHOGONAL: [Direction; 4] = [
         Self::North, Self::South, Self::West, Self::East
     ];
 
+    /// Все направления, включая `None`.
+    pub const ALL: [Direction; 11] = [
+        Self::None, Self::North, Self::South, Self::West, Self::East,
+        Self::NorthWest, Self::NorthEast, Self::SouthWest, Self::SouthEast,
+        Self::Up, Self::Down
+    ];
+
     /// Список всех 2D соседей (8-way).
     pub const ALL_2D: [Direction; 8] = [
         Self::North, Self::South, Self::West, Self::East,
@@ -50,12 +154,49 @@ impl Direction {
     ];
 }
 
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Direction {
+    type Err = UnknownDirection;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|d| d.as_str() == s)
+            .ok_or_else(|| UnknownDirection(s.to_string()))
+    }
+}
+
+impl From<Direction> for Offset {
+    fn from(dir: Direction) -> Offset {
+        let (dx, dy, dz) = dir.offset();
+        Offset::new(dx, dy, dz)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown direction: {0}")]
+pub struct UnknownDirection(pub String);
+
 /// Трейт для геометрических операций.
 /// Мы не пишем методы прямо в WorldPos, чтобы разделить хранение данных и логику.
-pub trait GridLogic {
+pub trait GridLogic: Sized {
+    /// Сдвиг без проверок: на краю мира координата заворачивается.
     fn shift(&self, dir: Direction) -> Self;
+    /// Сдвиг или None, если шаг выходит за край мира.
+    fn checked_shift(&self, dir: Direction) -> Option<Self>;
+    /// Сдвиг, прижатый к краю мира.
+    fn saturating_shift(&self, dir: Direction) -> Self;
     fn distance_squared(&self, other: Self) -> i64;
     fn manhattan_distance(&self, other: Self) -> i32;
+    /// Число ходов при 8-связном движении (диагональ = 1).
+    fn chebyshev_distance(&self, other: Self) -> i32;
+    /// Длина пути при 8-связном движении, где диагональ стоит √2.
+    fn octile_distance(&self, other: Self) -> f32;
     fn is_in_radius(&self, center: Self, radius: i32) -> bool;
 }
 
@@ -65,6 +206,14 @@ impl GridLogic for WorldPos {
         WorldPos::new(self.x() + dx, self.y() + dy, self.z() + dz)
     }
 
+    fn checked_shift(&self, dir: Direction) -> Option<Self> {
+        self.checked_add(dir.into())
+    }
+
+    fn saturating_shift(&self, dir: Direction) -> Self {
+        self.saturating_add(dir.into())
+    }
+
     fn distance_squared(&self, other: Self) -> i64 {
         let dx = (self.x() - other.x()) as i64;
         let dy = (self.y() - other.y()) as i64;
@@ -75,7 +224,69 @@ impl GridLogic for WorldPos {
         (self.x() - other.x()).abs() + (self.y() - other.y()).abs()
     }
 
+    fn chebyshev_distance(&self, other: Self) -> i32 {
+        (self.x() - other.x()).abs().max((self.y() - other.y()).abs())
+    }
+
+    fn octile_distance(&self, other: Self) -> f32 {
+        let dx = (self.x() - other.x()).abs();
+        let dy = (self.y() - other.y()).abs();
+        dx.max(dy) as f32 + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy) as f32
+    }
+
     fn is_in_radius(&self, center: Self, radius: i32) -> bool {
         self.distance_squared(center) <= (radius as i64 * radius as i64)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_roundtrip_and_opposite() {
+        for dir in Direction::ALL {
+            let (dx, dy, dz) = dir.offset();
+            assert_eq!(Direction::from_delta(dx, dy, dz), dir);
+            assert_eq!(dir.opposite().opposite(), dir);
+        }
+        assert_eq!(Direction::from_delta(5, -3, 0), Direction::NorthEast);
+        assert_eq!(Direction::from_delta(1, 0, 1), Direction::None);
+        assert_eq!(Direction::Up.opposite(), Direction::Down);
+        assert_eq!(Direction::from_positions(WorldPos::new(3, 3, 0), WorldPos::new(0, 3, 0)), Direction::West);
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut dir = Direction::North;
+        for _ in 0..8 {
+            assert_eq!(dir.rotate_cw().rotate_ccw(), dir);
+            dir = dir.rotate_cw();
+        }
+        assert_eq!(dir, Direction::North);
+        assert_eq!(Direction::North.rotate_cw().rotate_cw(), Direction::East);
+        assert_eq!(Direction::East.rotate_cw().rotate_cw().rotate_cw().rotate_cw(), Direction::West);
+        assert_eq!(Direction::Up.rotate_cw(), Direction::Up);
+        assert_eq!(Direction::SouthWest.components(), Some((Direction::South, Direction::West)));
+    }
+
+    #[test]
+    fn test_string_names_match_serde() {
+        for dir in Direction::ALL {
+            let json = serde_json::to_string(&dir).unwrap();
+            assert_eq!(json, format!("\"{}\"", dir.as_str()));
+            assert_eq!(dir.as_str().parse::<Direction>(), Ok(dir));
+        }
+        assert!("NorthEast".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn test_checked_shift_at_world_edge() {
+        let edge = WorldPos::new(WorldPos::X_MAX, 0, WorldPos::Z_MIN);
+        assert_eq!(edge.checked_shift(Direction::East), None);
+        assert_eq!(edge.checked_shift(Direction::Down), None);
+        assert_eq!(edge.checked_shift(Direction::West), Some(WorldPos::new(WorldPos::X_MAX - 1, 0, WorldPos::Z_MIN)));
+        assert_eq!(edge.saturating_shift(Direction::NorthEast), WorldPos::new(WorldPos::X_MAX, -1, WorldPos::Z_MIN));
+        assert_eq!(edge.shift(Direction::East).x(), WorldPos::X_MIN); // Старое поведение: заворот
+    }
+}
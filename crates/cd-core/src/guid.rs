@@ -1,5 +1,62 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
+
+/// Вид объекта — значение поля Type в GUID.
+/// Значения стабильны: они хранятся в БД и уходят клиентам.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum ObjectKind {
+    Player = 1,
+    Monster = 2,
+    Npc = 3,
+    Item = 4,
+    Projectile = 5,
+    /// Объект, привязанный к тайлу (сундук, алтарь, ловушка)
+    TileEntity = 6,
+}
+
+impl ObjectKind {
+    pub const ALL: [ObjectKind; 6] = [
+        Self::Player, Self::Monster, Self::Npc, Self::Item, Self::Projectile, Self::TileEntity
+    ];
+
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| *k as u8 == value)
+    }
+
+    /// Буква вида в компактной записи GUID.
+    pub fn letter(&self) -> char {
+        match self {
+            Self::Player => 'P',
+            Self::Monster => 'M',
+            Self::Npc => 'N',
+            Self::Item => 'I',
+            Self::Projectile => 'J',
+            Self::TileEntity => 'T',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.letter() == letter)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum GuidError {
+    #[error("shard {0} out of range")]
+    ShardOutOfRange(u64),
+    #[error("unknown object kind {0}")]
+    UnknownKind(u64),
+    #[error("generation {0} out of range")]
+    GenerationOutOfRange(u64),
+    #[error("index {0} out of range")]
+    IndexOutOfRange(u64),
+    #[error("invalid guid format")]
+    InvalidFormat,
+    #[error("expected {expected:?} guid, got type {actual}")]
+    WrongKind { expected: ObjectKind, actual: u8 },
+}
 
 /// Строгий тип для идентификатора объекта.
 /// Битовая раскладка (64 бита):
@@ -7,6 +64,7 @@ use std::fmt;
 #[repr(transparent)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
 pub struct ObjectGuid(u64);
 
 impl ObjectGuid {
@@ -59,6 +117,69 @@ impl ObjectGuid {
         ((self.0 >> Self::SHIFT_SHARD) & Self::MASK_SHARD) as u8
     }
 
+    /// Создает GUID с проверкой диапазонов (данные из БД, сети, админки).
+    /// Тип должен быть известным `ObjectKind`.
+    pub fn try_new(shard: u64, type_id: u64, generation: u64, index: u64) -> Result<Self, GuidError> {
+        if shard > Self::MASK_SHARD {
+            return Err(GuidError::ShardOutOfRange(shard));
+        }
+        if type_id > Self::MASK_TYPE || ObjectKind::from_u8(type_id as u8).is_none() {
+            return Err(GuidError::UnknownKind(type_id));
+        }
+        if generation > Self::MASK_GEN {
+            return Err(GuidError::GenerationOutOfRange(generation));
+        }
+        if index > Self::MASK_INDEX {
+            return Err(GuidError::IndexOutOfRange(index));
+        }
+        Ok(Self::new(shard as u8, type_id as u8, generation as u16, index as u32))
+    }
+
+    /// GUID объекта заданного вида.
+    #[inline]
+    pub fn of_kind(kind: ObjectKind, shard: u8, generation: u16, index: u32) -> Self {
+        Self::new(shard, kind as u8, generation, index)
+    }
+
+    pub fn player(shard: u8, generation: u16, index: u32) -> Self {
+        Self::of_kind(ObjectKind::Player, shard, generation, index)
+    }
+
+    pub fn monster(shard: u8, generation: u16, index: u32) -> Self {
+        Self::of_kind(ObjectKind::Monster, shard, generation, index)
+    }
+
+    pub fn item(shard: u8, generation: u16, index: u32) -> Self {
+        Self::of_kind(ObjectKind::Item, shard, generation, index)
+    }
+
+    pub fn projectile(shard: u8, generation: u16, index: u32) -> Self {
+        Self::of_kind(ObjectKind::Projectile, shard, generation, index)
+    }
+
+    pub fn tile_entity(shard: u8, generation: u16, index: u32) -> Self {
+        Self::of_kind(ObjectKind::TileEntity, shard, generation, index)
+    }
+
+    /// Вид объекта. `None` — неизвестный type_id (например, NIL).
+    #[inline]
+    pub fn kind(&self) -> Option<ObjectKind> {
+        ObjectKind::from_u8(self.type_id())
+    }
+
+    pub fn is_kind(&self, kind: ObjectKind) -> bool {
+        self.type_id() == kind as u8
+    }
+
+    /// Проверка на границе протокола: GUID должен быть нужного вида.
+    pub fn ensure_kind(self, kind: ObjectKind) -> Result<Self, GuidError> {
+        if self.is_kind(kind) {
+            Ok(self)
+        } else {
+            Err(GuidError::WrongKind { expected: kind, actual: self.type_id() })
+        }
+    }
+
     pub fn is_nil(&self) -> bool {
         self.0 == 0
     }
@@ -67,15 +188,120 @@ impl ObjectGuid {
     pub fn as_u64(&self) -> u64 {
         self.0
     }
+
+    /// Обратно из "сырого" значения (без проверок, как при чтении из БД)
+    pub const fn from_u64(raw: u64) -> Self {
+        Self(raw)
+    }
+}
+
+// --- Text Form ---
+// Display — десятичный u64, как и раньше (логи, БД, клиенты на это рассчитывают).
+// Компактная запись для людей — через `compact()`: <Вид><Шард>:<Индекс>[g<Поколение>]
+//   P1:4    — игрок, шард 1, индекс 4, поколение 0
+//   M0:17g3 — монстр, шард 0, индекс 17, поколение 3
+// Неизвестный вид пишется числом в скобках: [0]0:0 (это NIL).
+// FromStr принимает и компактную запись, и десятичную.
+
+impl fmt::Display for ObjectGuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl ObjectGuid {
+    /// Компактная запись для вывода: `format!("{}", guid.compact())` -> "P1:4g1"
+    pub fn compact(self) -> CompactGuid {
+        CompactGuid(self)
+    }
+}
+
+/// Обертка для вывода GUID в компактной записи.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CompactGuid(pub ObjectGuid);
+
+impl fmt::Display for CompactGuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guid = self.0;
+        match guid.kind() {
+            Some(kind) => write!(f, "{}", kind.letter())?,
+            None => write!(f, "[{}]", guid.type_id())?,
+        }
+        write!(f, "{}:{}", guid.shard_id(), guid.index())?;
+        if guid.generation() != 0 {
+            write!(f, "g{}", guid.generation())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ObjectGuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Guid({})", self.compact())
+    }
+}
+
+impl FromStr for ObjectGuid {
+    type Err = GuidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(raw) = s.parse::<u64>() {
+            return Ok(Self(raw));
+        }
+
+        let (type_id, rest) = match s.strip_prefix('[') {
+            Some(rest) => {
+                let (type_id, rest) = rest.split_once(']').ok_or(GuidError::InvalidFormat)?;
+                (type_id.parse::<u8>().map_err(|_| GuidError::InvalidFormat)?, rest)
+            }
+            None => {
+                let letter = s.chars().next().ok_or(GuidError::InvalidFormat)?;
+                let kind = ObjectKind::from_letter(letter).ok_or(GuidError::InvalidFormat)?;
+                (kind as u8, &s[letter.len_utf8()..])
+            }
+        };
+
+        let (shard, rest) = rest.split_once(':').ok_or(GuidError::InvalidFormat)?;
+        let (index, generation) = match rest.split_once('g') {
+            Some((index, generation)) => (index, generation),
+            None => (rest, "0"),
+        };
+
+        let num = |v: &str| v.parse::<u64>().map_err(|_| GuidError::InvalidFormat);
+        let (shard, index, generation) = (num(shard)?, num(index)?, num(generation)?);
+        if shard > Self::MASK_SHARD {
+            return Err(GuidError::ShardOutOfRange(shard));
+        }
+        if generation > Self::MASK_GEN {
+            return Err(GuidError::GenerationOutOfRange(generation));
+        }
+        if index > Self::MASK_INDEX {
+            return Err(GuidError::IndexOutOfRange(index));
+        }
+        Ok(Self::new(shard as u8, type_id, generation as u16, index as u32))
+    }
 }
 
-// Сериализация в JSON как число (или строка, если нужно для JS)
+// --- Serde ---
+// По умолчанию GUID в текстовых форматах пишется десятичной строкой (JS не умеет
+// u64 без потерь), а в бинарных (bincode и т.п.) — u64, как и WorldPos.
+// Другие форматы выбираются на поле через #[serde(with = "...")]:
+//   cd_core::guid::as_number  — всегда u64
+//   cd_core::guid::as_decimal — всегда десятичная строка
+//   cd_core::guid::as_compact — всегда компактная строка "P1:4g1"
+// В текстовых форматах чтение в любом режиме принимает любой из трех видов.
+// Бинарные форматы не умеют deserialize_any, там читается ровно то, что записано.
+
 impl Serialize for ObjectGuid {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.0.to_string())
+        if serializer.is_human_readable() {
+            as_decimal::serialize(self, serializer)
+        } else {
+            as_number::serialize(self, serializer)
+        }
     }
 }
 
@@ -84,27 +310,168 @@ impl<'de> Deserialize<'de> for ObjectGuid {
     where
         D: serde::Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        let val = s.parse::<u64>().map_err(serde::de::Error::custom)?;
-        Ok(Self(val))
+        as_number::deserialize(deserializer)
     }
 }
 
-impl fmt::Debug for ObjectGuid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Guid(ShardID:{}, TypeID:{}, Generation:{}, Index:{})",
-            self.shard_id(),
-            self.type_id(),
-            self.generation(),
-            self.index()
-        )
+struct GuidVisitor;
+
+impl serde::de::Visitor<'_> for GuidVisitor {
+    type Value = ObjectGuid;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a guid as u64, decimal string or compact string")
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<ObjectGuid, E> {
+        Ok(ObjectGuid(v))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<ObjectGuid, E> {
+        v.parse().map_err(E::custom)
     }
 }
 
-impl fmt::Display for ObjectGuid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+pub mod as_number {
+    use super::*;
+
+    pub fn serialize<S: serde::Serializer>(guid: &ObjectGuid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(guid.0)
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<ObjectGuid, D::Error> {
+        // Бинарные форматы не поддерживают deserialize_any
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(GuidVisitor)
+        } else {
+            deserializer.deserialize_u64(GuidVisitor)
+        }
+    }
+}
+
+pub mod as_decimal {
+    use super::*;
+
+    pub fn serialize<S: serde::Serializer>(guid: &ObjectGuid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&guid.0.to_string())
     }
-}
\ No newline at end of file
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<ObjectGuid, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(GuidVisitor)
+        } else {
+            deserializer.deserialize_str(GuidVisitor)
+        }
+    }
+}
+
+pub mod as_compact {
+    use super::*;
+
+    pub fn serialize<S: serde::Serializer>(guid: &ObjectGuid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&guid.compact())
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<ObjectGuid, D::Error> {
+        as_decimal::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_constructors() {
+        let guid = ObjectGuid::player(2, 7, 42);
+        assert_eq!(guid.kind(), Some(ObjectKind::Player));
+        assert_eq!((guid.shard_id(), guid.generation(), guid.index()), (2, 7, 42));
+        assert_eq!(ObjectGuid::item(0, 0, 1).kind(), Some(ObjectKind::Item));
+        assert_eq!(ObjectGuid::NIL.kind(), None);
+
+        assert!(guid.ensure_kind(ObjectKind::Player).is_ok());
+        assert_eq!(
+            guid.ensure_kind(ObjectKind::Monster),
+            Err(GuidError::WrongKind { expected: ObjectKind::Monster, actual: 1 })
+        );
+    }
+
+    #[test]
+    fn test_try_new_checks_ranges() {
+        assert_eq!(ObjectGuid::try_new(1, 2, 3, 4), Ok(ObjectGuid::monster(1, 3, 4)));
+        assert_eq!(ObjectGuid::try_new(256, 1, 0, 0), Err(GuidError::ShardOutOfRange(256)));
+        assert_eq!(ObjectGuid::try_new(0, 99, 0, 0), Err(GuidError::UnknownKind(99)));
+        assert_eq!(ObjectGuid::try_new(0, 1, 1 << 16, 0), Err(GuidError::GenerationOutOfRange(1 << 16)));
+        assert_eq!(ObjectGuid::try_new(0, 1, 0, 1 << 32), Err(GuidError::IndexOutOfRange(1 << 32)));
+    }
+
+    #[test]
+    fn test_compact_form_roundtrip() {
+        let cases = [
+            (ObjectGuid::player(1, 0, 4), "P1:4"),
+            (ObjectGuid::monster(0, 3, 17), "M0:17g3"),
+            (ObjectGuid::tile_entity(255, 65535, u32::MAX), "T255:4294967295g65535"),
+            (ObjectGuid::NIL, "[0]0:0"),
+            (ObjectGuid::new(2, 200, 1, 9), "[200]2:9g1"),
+        ];
+        for (guid, text) in cases {
+            assert_eq!(guid.compact().to_string(), text);
+            assert_eq!(text.parse::<ObjectGuid>(), Ok(guid));
+        }
+
+        // Display — десятичный, он тоже читается
+        let guid = ObjectGuid::item(1, 2, 3);
+        assert_eq!(guid.to_string(), guid.as_u64().to_string());
+        assert_eq!(guid.to_string().parse::<ObjectGuid>(), Ok(guid));
+        assert_eq!(format!("{:?}", guid), "Guid(I1:3g2)");
+
+        for bad in ["", "P", "P1", "Z1:2", "P1:x", "P1:2g", "[1:2", "P256:1"] {
+            assert!(bad.parse::<ObjectGuid>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_serde_modes() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Packet {
+            default: ObjectGuid,
+            #[serde(with = "as_number")]
+            number: ObjectGuid,
+            #[serde(with = "as_compact")]
+            compact: ObjectGuid,
+        }
+
+        let guid = ObjectGuid::player(1, 1, 4);
+        let packet = Packet { default: guid, number: guid, compact: guid };
+        let json = serde_json::to_string(&packet).unwrap();
+        let raw = guid.as_u64();
+        assert_eq!(json, format!(r#"{{"default":"{raw}","number":{raw},"compact":"P1:4g1"}}"#));
+        assert_eq!(serde_json::from_str::<Packet>(&json).unwrap(), packet);
+
+        // Любое поле читает любой вид записи
+        let mixed = format!(r#"{{"default":"P1:4g1","number":"{raw}","compact":{raw}}}"#);
+        assert_eq!(serde_json::from_str::<Packet>(&mixed).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_serde_binary() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Packet {
+            default: ObjectGuid,
+            #[serde(with = "as_number")]
+            number: ObjectGuid,
+            #[serde(with = "as_decimal")]
+            decimal: ObjectGuid,
+            #[serde(with = "as_compact")]
+            compact: ObjectGuid,
+        }
+
+        let guid = ObjectGuid::monster(2, 7, 99);
+        assert_eq!(bincode::serialize(&guid).unwrap(), guid.as_u64().to_le_bytes());
+        assert_eq!(bincode::deserialize::<ObjectGuid>(&bincode::serialize(&guid).unwrap()).unwrap(), guid);
+
+        let packet = Packet { default: guid, number: guid, decimal: guid, compact: guid };
+        let bin = bincode::serialize(&packet).unwrap();
+        assert_eq!(bincode::deserialize::<Packet>(&bin).unwrap(), packet);
+    }
+}
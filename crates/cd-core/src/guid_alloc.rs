@@ -0,0 +1,272 @@
+use std::collections::{BTreeMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::{ObjectGuid, ObjectKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum GuidAllocError {
+    #[error("GUID index space exhausted for {0:?}")]
+    Exhausted(ObjectKind),
+    #[error("pool {kind}: {alive} alive flags for {generations} generations")]
+    LengthMismatch { kind: u8, generations: usize, alive: usize },
+    #[error("pool {kind}: free index {index} out of range")]
+    FreeOutOfRange { kind: u8, index: u32 },
+    #[error("pool {kind}: free index {index} listed twice")]
+    DuplicateFree { kind: u8, index: u32 },
+    #[error("pool {kind}: free index {index} is still alive")]
+    FreeIsAlive { kind: u8, index: u32 },
+}
+
+/// Пул индексов одного типа объектов.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Pool {
+    // Текущее поколение каждого выданного индекса (index - 1 -> generation)
+    generations: Vec<u16>,
+    // Занят ли индекс сейчас
+    alive: Vec<bool>,
+    // Освобожденные индексы, готовые к повторной выдаче (LIFO)
+    free: Vec<u32>,
+}
+
+/// Выдает GUID'ы для одного шарда.
+///
+/// Индексы ведутся отдельно для каждого вида объектов. Освобожденный индекс переиспользуется,
+/// но с увеличенным поколением, поэтому старый GUID больше не считается живым
+/// (`is_alive`) и не совпадет с новым.
+///
+/// Состояние сериализуемо: сохраните его при остановке и восстановите при старте —
+/// тогда GUID'ы останутся уникальными между перезапусками.
+/// Противоречивое состояние (битый или подправленный руками файл) не десериализуется.
+/// Сохранять нужно раньше, чем выданный GUID попадет в другое хранилище:
+/// иначе после падения аллокатор выдаст его повторно.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawAllocator")]
+pub struct GuidAllocator {
+    shard: u8,
+    pools: BTreeMap<u8, Pool>,
+}
+
+/// Состояние как оно лежит в файле, до проверки.
+#[derive(Deserialize)]
+struct RawAllocator {
+    shard: u8,
+    pools: BTreeMap<u8, Pool>,
+}
+
+/// Следующий новый индекс при `len` уже выданных. None — индексы кончились.
+fn next_index(len: usize) -> Option<u32> {
+    u32::try_from(len + 1).ok()
+}
+
+impl TryFrom<RawAllocator> for GuidAllocator {
+    type Error = GuidAllocError;
+
+    fn try_from(raw: RawAllocator) -> Result<Self, Self::Error> {
+        for (&kind, pool) in &raw.pools {
+            pool.validate(kind)?;
+        }
+        Ok(Self { shard: raw.shard, pools: raw.pools })
+    }
+}
+
+impl Pool {
+    /// Иначе allocate выдаст живой индекс второй раз или упадет на индексации.
+    fn validate(&self, kind: u8) -> Result<(), GuidAllocError> {
+        if self.alive.len() != self.generations.len() {
+            return Err(GuidAllocError::LengthMismatch {
+                kind,
+                generations: self.generations.len(),
+                alive: self.alive.len(),
+            });
+        }
+        let mut seen = HashSet::with_capacity(self.free.len());
+        for &index in &self.free {
+            if index == 0 || index as usize > self.alive.len() {
+                return Err(GuidAllocError::FreeOutOfRange { kind, index });
+            }
+            if !seen.insert(index) {
+                return Err(GuidAllocError::DuplicateFree { kind, index });
+            }
+            if self.alive[index as usize - 1] {
+                return Err(GuidAllocError::FreeIsAlive { kind, index });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl GuidAllocator {
+    pub fn new(shard: u8) -> Self {
+        Self { shard, pools: BTreeMap::new() }
+    }
+
+    pub fn shard(&self) -> u8 {
+        self.shard
+    }
+
+    /// Выдать новый GUID для вида объектов.
+    /// Индексы начинаются с 1, чтобы никогда не совпасть с `ObjectGuid::NIL`.
+    /// Ошибка — у вида закончились все 2^32 - 1 индексов.
+    pub fn allocate(&mut self, kind: ObjectKind) -> Result<ObjectGuid, GuidAllocError> {
+        let type_id = kind as u8;
+        let pool = self.pools.entry(type_id).or_default();
+
+        let index = match pool.free.pop() {
+            Some(index) => index,
+            None => {
+                let next = next_index(pool.generations.len()).ok_or(GuidAllocError::Exhausted(kind))?;
+                pool.generations.push(0);
+                pool.alive.push(false);
+                next
+            }
+        };
+
+        let slot = index as usize - 1;
+        pool.alive[slot] = true;
+        Ok(ObjectGuid::of_kind(kind, self.shard, pool.generations[slot], index))
+    }
+
+    /// Вернуть GUID в пул. Поколение индекса увеличивается сразу,
+    /// так что все копии этого GUID становятся устаревшими.
+    /// Вернет false для чужого, уже освобожденного или устаревшего GUID.
+    pub fn free(&mut self, guid: ObjectGuid) -> bool {
+        if !self.is_alive(guid) {
+            return false;
+        }
+        let Some(pool) = self.pools.get_mut(&guid.type_id()) else { return false };
+
+        let slot = guid.index() as usize - 1;
+        pool.alive[slot] = false;
+        pool.generations[slot] = pool.generations[slot].wrapping_add(1);
+        pool.free.push(guid.index());
+        true
+    }
+
+    /// Освободить все живые GUID'ы вида. Вернет, сколько освобождено.
+    ///
+    /// Нужно после восстановления состояния, если объекты этого вида не переживают
+    /// перезапуск (сессии игроков): иначе их индексы навсегда останутся занятыми.
+    pub fn free_all(&mut self, kind: ObjectKind) -> usize {
+        let Some(pool) = self.pools.get_mut(&(kind as u8)) else { return 0 };
+        let mut freed = 0;
+        for slot in 0..pool.alive.len() {
+            if pool.alive[slot] {
+                pool.alive[slot] = false;
+                pool.generations[slot] = pool.generations[slot].wrapping_add(1);
+                pool.free.push(slot as u32 + 1);
+                freed += 1;
+            }
+        }
+        freed
+    }
+
+    /// Выдан ли этот GUID и не освобожден ли с тех пор.
+    pub fn is_alive(&self, guid: ObjectGuid) -> bool {
+        if guid.shard_id() != self.shard || guid.index() == 0 {
+            return false;
+        }
+        let Some(pool) = self.pools.get(&guid.type_id()) else { return false };
+        let slot = guid.index() as usize - 1;
+        pool.alive.get(slot).copied().unwrap_or(false) && pool.generations[slot] == guid.generation()
+    }
+
+    /// Сколько GUID'ов вида сейчас выдано.
+    pub fn live_count(&self, kind: ObjectKind) -> usize {
+        self.pools.get(&(kind as u8)).map_or(0, |p| p.alive.iter().filter(|&&a| a).count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indices_are_per_type_and_reused_with_new_generation() {
+        let mut guids = GuidAllocator::new(3);
+        let a = guids.allocate(ObjectKind::Player).unwrap();
+        let b = guids.allocate(ObjectKind::Player).unwrap();
+        let other = guids.allocate(ObjectKind::Item).unwrap();
+
+        assert_eq!((a.index(), b.index(), other.index()), (1, 2, 1));
+        assert_eq!(a.shard_id(), 3);
+        assert!(!a.is_nil());
+
+        assert!(guids.free(a));
+        assert!(!guids.free(a)); // Повторно — нельзя
+        assert!(!guids.is_alive(a));
+
+        let reused = guids.allocate(ObjectKind::Player).unwrap();
+        assert_eq!(reused.index(), a.index());
+        assert_eq!(reused.generation(), a.generation() + 1);
+        assert_ne!(reused, a);
+        assert!(guids.is_alive(reused));
+        assert!(!guids.free(a)); // Устаревший GUID не освобождает новый
+        assert_eq!(guids.live_count(ObjectKind::Player), 2);
+    }
+
+    #[test]
+    fn test_state_survives_restart() {
+        let mut guids = GuidAllocator::new(0);
+        let a = guids.allocate(ObjectKind::Player).unwrap();
+        let b = guids.allocate(ObjectKind::Player).unwrap();
+        guids.free(a);
+
+        let saved = serde_json::to_string(&guids).unwrap();
+        let mut restored: GuidAllocator = serde_json::from_str(&saved).unwrap();
+        assert_eq!(restored, guids);
+
+        assert!(restored.is_alive(b));
+        assert!(!restored.is_alive(a));
+        let next = restored.allocate(ObjectKind::Player).unwrap();
+        assert_ne!(next, a);
+        assert_ne!(next, b);
+    }
+
+    #[test]
+    fn test_free_all_after_restart() {
+        let mut guids = GuidAllocator::new(0);
+        let a = guids.allocate(ObjectKind::Player).unwrap();
+        let b = guids.allocate(ObjectKind::Player).unwrap();
+        let item = guids.allocate(ObjectKind::Item).unwrap();
+
+        let saved = serde_json::to_string(&guids).unwrap();
+        let mut restored: GuidAllocator = serde_json::from_str(&saved).unwrap();
+        // Сессии прошлого процесса мертвы, предметы — нет
+        assert_eq!(restored.free_all(ObjectKind::Player), 2);
+        assert_eq!(restored.free_all(ObjectKind::Player), 0);
+        assert!(!restored.is_alive(a) && !restored.is_alive(b));
+        assert!(restored.is_alive(item));
+        assert_eq!(restored.live_count(ObjectKind::Player), 0);
+
+        // Индексы переиспользуются, но GUID'ы не совпадают со старыми
+        let next = restored.allocate(ObjectKind::Player).unwrap();
+        assert!(next.index() <= 2);
+        assert_ne!(next, a);
+        assert_ne!(next, b);
+    }
+
+    #[test]
+    fn test_inconsistent_state_is_rejected() {
+        let load = |json: &str| serde_json::from_str::<GuidAllocator>(json);
+        let pool = |body: &str| format!(r#"{{"shard":0,"pools":{{"1":{}}}}}"#, body);
+
+        assert!(load(&pool(r#"{"generations":[0,0],"alive":[true,false],"free":[2]}"#)).is_ok());
+
+        let bad = [
+            r#"{"generations":[0,0],"alive":[true],"free":[]}"#,         // Длины разошлись
+            r#"{"generations":[0],"alive":[false],"free":[0]}"#,         // Индекс 0
+            r#"{"generations":[0],"alive":[false],"free":[2]}"#,         // Вне пула
+            r#"{"generations":[0,0],"alive":[false,false],"free":[1,1]}"#, // Дубликат
+            r#"{"generations":[0],"alive":[true],"free":[1]}"#,          // Свободен и жив
+        ];
+        for body in bad {
+            assert!(load(&pool(body)).is_err(), "accepted {}", body);
+        }
+    }
+
+    #[test]
+    fn test_index_space_exhaustion() {
+        assert_eq!(next_index(0), Some(1));
+        assert_eq!(next_index(u32::MAX as usize - 1), Some(u32::MAX));
+        assert_eq!(next_index(u32::MAX as usize), None);
+    }
+}
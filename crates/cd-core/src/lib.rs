@@ -1,8 +1,13 @@
 pub mod guid;
+pub mod guid_alloc;
 pub mod geo;
 pub mod grid;
+pub mod rng;
+pub mod shape;
 
 // Реэкспорт для удобства
-pub use guid::ObjectGuid;
-pub use geo::WorldPos;
-pub use grid::*;
\ No newline at end of file
+pub use guid::{CompactGuid, GuidError, ObjectGuid, ObjectKind};
+pub use guid_alloc::{GuidAllocError, GuidAllocator};
+pub use geo::{Offset, PosError, WorldBounds, WorldPos};
+pub use grid::*;
+pub use rng::Rng;
\ No newline at end of file
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+/// Детерминированный генератор псевдослучайных чисел (SplitMix64).
+/// Один и тот же seed всегда дает одну и ту же последовательность на любой платформе —
+/// это нужно для реплеев и воспроизводимых тестов симуляции.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Независимый поток, определяемый только seed и ключами.
+    /// Разные ключи дают некоррелированные потоки; порядок ключей важен.
+    pub fn from_keys(seed: u64, keys: &[u64]) -> Self {
+        let mut state = mix64(seed);
+        for &key in keys {
+            state = mix64(state ^ mix64(key.wrapping_add(0x9E37_79B9_7F4A_7C15)));
+        }
+        Self::new(state)
+    }
+
+    /// Текущее состояние (для сохранения вместе с миром).
+    pub const fn state(&self) -> u64 {
+        self.state
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix64(self.state)
+    }
+
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Число в диапазоне [0, bound). Для bound == 0 возвращает 0.
+    #[inline]
+    pub fn below(&mut self, bound: u32) -> u32 {
+        // Multiply-shift: без деления и почти без смещения для малых bound
+        ((self.next_u32() as u64 * bound as u64) >> 32) as u32
+    }
+
+    /// Событие с вероятностью `percent` из 100.
+    #[inline]
+    pub fn chance(&mut self, percent: u8) -> bool {
+        self.below(100) < percent as u32
+    }
+}
+
+/// Финализатор SplitMix64: хорошо перемешивает биты, биекция на u64.
+#[inline]
+const fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+
+        let mut c = Rng::new(43);
+        assert_ne!(Rng::new(42).next_u64(), c.next_u64());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            assert!(rng.below(10) < 10);
+        }
+        assert_eq!(rng.below(0), 0);
+        assert!(!rng.chance(0));
+        assert!(rng.chance(100));
+    }
+
+    #[test]
+    fn test_keyed_streams() {
+        let a = Rng::from_keys(1, &[10, 20]).next_u64();
+        assert_eq!(a, Rng::from_keys(1, &[10, 20]).next_u64());
+        assert_ne!(a, Rng::from_keys(1, &[20, 10]).next_u64());
+        assert_ne!(a, Rng::from_keys(2, &[10, 20]).next_u64());
+        assert_ne!(Rng::from_keys(1, &[]).next_u64(), Rng::from_keys(1, &[0]).next_u64());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{Direction, WorldPos};
+
+// Итераторы по фигурам на сетке. Все ленивые и без аллокаций:
+// AoE заклинаний, генераторы и FOV обходят клетки, не собирая их в Vec.
+// Фигуры плоские — z берется из центра/начала.
+
+/// Прямоугольник [min, max] включительно, построчно.
+pub fn rect(min: WorldPos, max: WorldPos) -> RectIter {
+    RectIter {
+        x0: min.x(),
+        x1: max.x(),
+        y1: max.y(),
+        z: min.z(),
+        x: min.x(),
+        y: min.y(),
+    }
+}
+
+/// Заполненный круг: d² <= r².
+pub fn circle(center: WorldPos, radius: i32) -> RingIter {
+    ring(center, -1, radius)
+}
+
+/// Контур круга толщиной в клетку: (r - 1)² < d² <= r².
+pub fn hollow_circle(center: WorldPos, radius: i32) -> RingIter {
+    ring(center, radius - 1, radius)
+}
+
+/// Кольцо: inner² < d² <= outer². При inner < 0 центр входит в кольцо.
+pub fn ring(center: WorldPos, inner: i32, outer: i32) -> RingIter {
+    let outer = outer.max(0);
+    let (cx, cy, _) = center.xyz();
+    RingIter {
+        center,
+        inner_sq: if inner < 0 { -1 } else { inner as i64 * inner as i64 },
+        outer_sq: outer as i64 * outer as i64,
+        area: rect(WorldPos::new(cx - outer, cy - outer, center.z()), WorldPos::new(cx + outer, cy + outer, center.z())),
+    }
+}
+
+/// Конус из `origin` в направлении `dir` (раствор 90°), до `radius` включительно.
+/// Сам `origin` не входит. Для `Up`/`Down`/`None` конус пустой.
+pub fn cone(origin: WorldPos, dir: Direction, radius: i32) -> ConeIter {
+    let (ox, oy, _) = dir.offset();
+    ConeIter { origin, ox, oy, disk: circle(origin, radius) }
+}
+
+/// Линия от `a` до `b` включительно (Брезенхэм).
+///
+/// Набор клеток не зависит от направления: `line(a, b)` — это `line(b, a)` в обратном порядке.
+/// На этом держится симметрия видимости ("вижу" == "виден").
+pub fn line(a: WorldPos, b: WorldPos) -> LineIter {
+    // Считаем всегда от меньшей точки; обход в обратную сторону — через индекс
+    let reversed = (b.x(), b.y()) < (a.x(), a.y());
+    let (start, end) = if reversed { (b, a) } else { (a, b) };
+    let dx = end.x() - start.x();
+    let dy = end.y() - start.y();
+    let steps = dx.abs().max(dy.abs());
+
+    LineIter { start, dx, dy, steps, front: 0, back: steps + 1, reversed }
+}
+
+// --- Iterators ---
+
+#[derive(Debug, Clone)]
+pub struct RectIter {
+    x0: i32,
+    x1: i32,
+    y1: i32,
+    z: i32,
+    x: i32,
+    y: i32,
+}
+
+impl Iterator for RectIter {
+    type Item = WorldPos;
+
+    fn next(&mut self) -> Option<WorldPos> {
+        if self.y > self.y1 || self.x0 > self.x1 {
+            return None;
+        }
+        let pos = WorldPos::new(self.x, self.y, self.z);
+        if self.x < self.x1 {
+            self.x += 1;
+        } else {
+            self.x = self.x0;
+            self.y += 1;
+        }
+        Some(pos)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RingIter {
+    center: WorldPos,
+    inner_sq: i64,
+    outer_sq: i64,
+    area: RectIter,
+}
+
+impl Iterator for RingIter {
+    type Item = WorldPos;
+
+    fn next(&mut self) -> Option<WorldPos> {
+        let (cx, cy) = (self.center.x() as i64, self.center.y() as i64);
+        self.area.find(|p| {
+            let (dx, dy) = (p.x() as i64 - cx, p.y() as i64 - cy);
+            let d = dx * dx + dy * dy;
+            d > self.inner_sq && d <= self.outer_sq
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConeIter {
+    origin: WorldPos,
+    ox: i32,
+    oy: i32,
+    disk: RingIter,
+}
+
+impl Iterator for ConeIter {
+    type Item = WorldPos;
+
+    fn next(&mut self) -> Option<WorldPos> {
+        if (self.ox, self.oy) == (0, 0) {
+            return None;
+        }
+        let (ox, oy) = (self.ox as i64, self.oy as i64);
+        let (x0, y0) = (self.origin.x() as i64, self.origin.y() as i64);
+        self.disk.find(|p| {
+            let (vx, vy) = (p.x() as i64 - x0, p.y() as i64 - y0);
+            let dot = vx * ox + vy * oy;
+            // Угол до оси не больше 45°: cos² >= 1/2
+            dot > 0 && 2 * dot * dot >= (vx * vx + vy * vy) * (ox * ox + oy * oy)
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LineIter {
+    start: WorldPos,
+    dx: i32,
+    dy: i32,
+    steps: i32,
+    // Еще не выданные номера клеток в порядке обхода: [front, back)
+    front: i32,
+    back: i32,
+    reversed: bool,
+}
+
+impl LineIter {
+    /// Клетка с номером `t` от начала нормализованной линии.
+    fn point(&self, t: i32) -> WorldPos {
+        let (x0, y0, z) = self.start.xyz();
+        if self.steps == 0 {
+            return self.start;
+        }
+        // Координата по второстепенной оси округляется половиной вверх — одинаково в обе стороны
+        let n = self.steps as i64;
+        let minor = |d: i32| (2 * t as i64 * d as i64 + n).div_euclid(2 * n) as i32;
+        if self.dx.abs() >= self.dy.abs() {
+            WorldPos::new(x0 + t * self.dx.signum(), y0 + minor(self.dy), z)
+        } else {
+            WorldPos::new(x0 + minor(self.dx), y0 + t * self.dy.signum(), z)
+        }
+    }
+}
+
+impl Iterator for LineIter {
+    type Item = WorldPos;
+
+    fn next(&mut self) -> Option<WorldPos> {
+        if self.front >= self.back {
+            return None;
+        }
+        let i = self.front;
+        self.front += 1;
+        let t = if self.reversed { self.steps - i } else { i };
+        Some(self.point(t))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.back - self.front) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for LineIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(x: i32, y: i32) -> WorldPos {
+        WorldPos::new(x, y, 0)
+    }
+
+    #[test]
+    fn test_rect_and_circle_counts() {
+        assert_eq!(rect(p(-1, -1), p(1, 2)).count(), 12);
+        assert_eq!(rect(p(1, 1), p(0, 0)).count(), 0);
+
+        assert_eq!(circle(p(5, 5), 0).collect::<Vec<_>>(), vec![p(5, 5)]);
+        assert_eq!(circle(p(0, 0), 1).count(), 5);
+        assert_eq!(circle(p(0, 0), 2).count(), 13);
+
+        // Контур + внутренность = круг
+        let inner = circle(p(0, 0), 3).count();
+        assert_eq!(hollow_circle(p(0, 0), 4).count() + inner, circle(p(0, 0), 4).count());
+        assert!(hollow_circle(p(0, 0), 4).all(|q| q.x().abs().max(q.y().abs()) >= 3));
+    }
+
+    #[test]
+    fn test_cone_points_away_from_origin() {
+        let east: Vec<_> = cone(p(0, 0), Direction::East, 3).collect();
+        assert!(east.contains(&p(3, 0)));
+        assert!(east.contains(&p(2, 2)));
+        assert!(!east.contains(&p(0, 0)));
+        assert!(east.iter().all(|q| q.x() > 0 && q.y().abs() <= q.x()));
+
+        let ne: Vec<_> = cone(p(0, 0), Direction::NorthEast, 2).collect();
+        assert!(ne.contains(&p(1, -1)) && ne.contains(&p(2, 0)) && ne.contains(&p(0, -2)));
+        assert!(!ne.contains(&p(-1, -1)));
+
+        assert_eq!(cone(p(0, 0), Direction::Up, 5).count(), 0);
+    }
+
+    #[test]
+    fn test_line_is_symmetric_and_connected() {
+        for (bx, by) in [(7, 3), (-5, 2), (3, -8), (0, 0), (-4, -4), (6, 0), (1, 2)] {
+            let a = p(0, 0);
+            let b = p(bx, by);
+            let forward: Vec<_> = line(a, b).collect();
+            let mut backward: Vec<_> = line(b, a).collect();
+            backward.reverse();
+
+            assert_eq!(forward, backward);
+            assert_eq!(forward.first(), Some(&a));
+            assert_eq!(forward.last(), Some(&b));
+            assert_eq!(line(a, b).len(), forward.len());
+            assert!(forward.windows(2).all(|w| (w[0].x() - w[1].x()).abs() <= 1 && (w[0].y() - w[1].y()).abs() <= 1));
+        }
+    }
+
+    #[test]
+    fn test_distance_metrics() {
+        use crate::GridLogic;
+        assert_eq!(p(0, 0).chebyshev_distance(p(3, -7)), 7);
+        assert_eq!(p(0, 0).chebyshev_distance(p(0, 0)), 0);
+        let octile = p(0, 0).octile_distance(p(3, 4));
+        assert!((octile - (4.0 + 3.0 * (std::f32::consts::SQRT_2 - 1.0))).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,13 @@
+use cd_core::WorldPos;
+
+/// Намерение шагнуть в соседнюю клетку (или по лестнице на другой этаж).
+/// Пишут ввод и ИИ; система движения проверяет шаг, исполняет его и снимает намерение.
+/// Новое намерение до конца тика заменяет старое.
+///
+/// Ввод вставляет намерение до систем, и шаг исполняется в том же тике.
+/// ИИ вставляет его через `commands`, которые применяются после всех стадий,
+/// поэтому шаг ИИ исполняется на следующем тике (задержка в один тик).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveIntent {
+    pub target: WorldPos,
+}
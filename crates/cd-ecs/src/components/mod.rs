@@ -1,4 +1,6 @@
 pub mod flags;
+pub mod intent;
 pub mod state;
 pub use flags::*;
+pub use intent::*;
 pub use state::*;
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use cd_core::{Rng, WorldPos};
+use cd_map::{MaterialRegistry, WorldMap, CHUNK_SIZE};
+use crate::events::GameEvent;
+
+/// Доступ к миру для систем окружения.
+pub struct EnvContext<'a> {
+    pub map: &'a WorldMap,
+    pub materials: &'a MaterialRegistry,
+    pub events: &'a mut Vec<GameEvent>,
+    pub rng: &'a mut Rng,
+    pub tick: u64,
+}
+
+/// Система окружения (жидкости, рост растений, газ).
+/// Получает случайные тайлы только из активных чанков.
+pub trait EnvironmentSystem: Send {
+    fn name(&self) -> &'static str;
+
+    /// Случайное обновление одного тайла.
+    fn random_tick(&mut self, pos: WorldPos, ctx: &mut EnvContext);
+}
+
+/// Почему чанк не спит.
+#[derive(Debug, Clone, Copy, Default)]
+struct ActiveChunk {
+    /// Рядом есть игрок (пересчитывается каждый тик)
+    near_player: bool,
+    /// До какого тика чанк бодрствует после изменения
+    awake_until: u64,
+}
+
+/// Планировщик чанков: держит "активное множество" и раздает
+/// системам окружения бюджет случайных тиков.
+///
+/// Чанк активен, если рядом игрок или его недавно меняли.
+/// Все остальные чанки спят и не стоят ничего.
+pub struct ChunkScheduler {
+    active: HashMap<WorldPos, ActiveChunk>,
+    systems: Vec<Box<dyn EnvironmentSystem>>,
+    // Смещение для round-robin, когда чанков больше, чем бюджета
+    cursor: usize,
+
+    /// Радиус (в чанках) вокруг игрока, в котором чанки активны.
+    pub player_radius: i32,
+    /// Сколько тиков чанк не спит после изменения.
+    pub wake_ticks: u64,
+    /// Случайных тайлов на чанк за тик.
+    pub random_ticks_per_chunk: u32,
+    /// Общий лимит случайных обновлений за тик.
+    pub tick_budget: u32,
+}
+
+impl Default for ChunkScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkScheduler {
+    pub fn new() -> Self {
+        Self {
+            active: HashMap::new(),
+            systems: Vec::new(),
+            cursor: 0,
+            player_radius: 2,
+            wake_ticks: 100,
+            random_ticks_per_chunk: 3,
+            tick_budget: 4096,
+        }
+    }
+
+    pub fn register(&mut self, system: Box<dyn EnvironmentSystem>) {
+        self.systems.push(system);
+    }
+
+    /// Разбудить чанк (в нем что-то изменилось).
+    pub fn mark_changed(&mut self, chunk_key: WorldPos, tick: u64) {
+        let entry = self.active.entry(chunk_key).or_default();
+        entry.awake_until = entry.awake_until.max(tick + self.wake_ticks);
+    }
+
+    /// Пересчитать активное множество по позициям игроков.
+    /// Чанки без игроков и с истекшим таймером засыпают.
+    pub fn update_active(&mut self, players: impl IntoIterator<Item = WorldPos>, tick: u64) {
+        for chunk in self.active.values_mut() {
+            chunk.near_player = false;
+        }
+
+        let r = self.player_radius;
+        for pos in players {
+            let (cx, cy, cz) = pos.chunk_key().xyz();
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    let key = WorldPos::new(cx + dx, cy + dy, cz);
+                    self.active.entry(key).or_default().near_player = true;
+                }
+            }
+        }
+
+        self.active.retain(|_, c| c.near_player || c.awake_until > tick);
+    }
+
+    pub fn is_active(&self, chunk_key: WorldPos) -> bool {
+        self.active.contains_key(&chunk_key)
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Раздать случайные тики активным чанкам.
+    /// `rng` — поток на этот тик (`WorldRng::stream`), своего состояния планировщик не держит.
+    pub fn run(
+        &mut self,
+        map: &WorldMap,
+        materials: &MaterialRegistry,
+        events: &mut Vec<GameEvent>,
+        rng: &mut Rng,
+        tick: u64,
+    ) {
+        if self.systems.is_empty() || self.active.is_empty() {
+            return;
+        }
+
+        // Фиксированный порядок чанков — детерминизм RNG
+        let mut chunks: Vec<WorldPos> = self.active.keys().copied().collect();
+        chunks.sort_unstable_by_key(|k| (k.z(), k.y(), k.x()));
+
+        let per_chunk = self.random_ticks_per_chunk.max(1);
+        let max_chunks = ((self.tick_budget / per_chunk) as usize).min(chunks.len());
+        let start = self.cursor % chunks.len();
+        self.cursor = start + max_chunks;
+
+        let mut ctx = EnvContext { map, materials, events, rng, tick };
+
+        for i in 0..max_chunks {
+            let (cx, cy, cz) = chunks[(start + i) % chunks.len()].xyz();
+            for _ in 0..per_chunk {
+                let lx = ctx.rng.below(CHUNK_SIZE as u32) as i32;
+                let ly = ctx.rng.below(CHUNK_SIZE as u32) as i32;
+                let pos = WorldPos::new(cx * CHUNK_SIZE + lx, cy * CHUNK_SIZE + ly, cz);
+
+                for system in &mut self.systems {
+                    system.random_tick(pos, &mut ctx);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter(std::sync::Arc<std::sync::Mutex<Vec<WorldPos>>>);
+
+    impl EnvironmentSystem for Counter {
+        fn name(&self) -> &'static str {
+            "counter"
+        }
+
+        fn random_tick(&mut self, pos: WorldPos, _ctx: &mut EnvContext) {
+            self.0.lock().unwrap().push(pos);
+        }
+    }
+
+    #[test]
+    fn test_chunks_sleep_without_players() {
+        let mut sched = ChunkScheduler::new();
+        sched.player_radius = 1;
+
+        sched.update_active([WorldPos::new(0, 0, 0)], 0);
+        assert_eq!(sched.active_count(), 9);
+        assert!(sched.is_active(WorldPos::new(-1, 1, 0)));
+
+        // Игрок ушел — все уснули
+        sched.update_active([], 1);
+        assert_eq!(sched.active_count(), 0);
+    }
+
+    #[test]
+    fn test_changed_chunk_stays_awake_for_a_while() {
+        let mut sched = ChunkScheduler::new();
+        sched.wake_ticks = 10;
+        let key = WorldPos::new(100, 100, 0);
+
+        sched.mark_changed(key, 5);
+        sched.update_active([], 14);
+        assert!(sched.is_active(key));
+        sched.update_active([], 15);
+        assert!(!sched.is_active(key));
+    }
+
+    #[test]
+    fn test_random_ticks_stay_inside_active_chunks() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut sched = ChunkScheduler::new();
+        sched.register(Box::new(Counter(seen.clone())));
+        sched.player_radius = 0;
+        sched.random_ticks_per_chunk = 5;
+
+        let map = WorldMap::new();
+        let materials = MaterialRegistry::new();
+        let mut events = Vec::new();
+
+        sched.update_active([WorldPos::new(-20, 40, 0)], 0);
+        sched.run(&map, &materials, &mut events, &mut Rng::new(7), 0);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 5);
+        let chunk = WorldPos::new(-20, 40, 0).chunk_key();
+        assert!(seen.iter().all(|p| p.chunk_key() == chunk));
+    }
+}
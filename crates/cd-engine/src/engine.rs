@@ -1,12 +1,39 @@
 use crate::input::InputCmd;
-use crate::systems;
-use cd_core::{ObjectGuid, WorldPos};
-use cd_ecs::components::{Position, Name, Render, Stats};
-use cd_map::{WorldMap, SpatialGrid};
+use cd_core::{GridLogic, ObjectGuid, ObjectKind, WorldBounds, WorldPos};
+use cd_ecs::components::{Controller, MoveIntent, Position, Name, Render, Stats};
+use cd_map::{DamageOutcome, DimensionRegistry, MaterialRegistry, WorldMap, SpatialGrid, ZoneRegistry};
 use hecs::{World, Entity, CommandBuffer};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::sync::{PoisonError, RwLock};
 use tracing::{info, warn};
 use crate::registry::EntityRegistry;
+use crate::events::GameEvent;
+use crate::systems::fire::FireSystem;
+use crate::chunk_scheduler::{ChunkScheduler, EnvironmentSystem};
+use crate::instance::{InstanceId, InstanceManager, InstanceTemplate};
+use crate::rng::WorldRng;
+use crate::schedule::{Resources, ScheduleError, Scheduler, System};
+use crate::systems::movement::MovementSystem;
+
+/// Где появляются новые игроки (и куда выводят тех, кому некуда вернуться).
+pub const SPAWN_POS: WorldPos = WorldPos::new(0, 0, 0);
+
+/// Уровни постоянного мира: поверхность, подземелья под ней и все, что выше.
+/// Резервируются при создании движка, поэтому инстансы получают z только ниже
+/// и никогда не лягут поверх мира (и не сотрут его при удалении).
+pub const WORLD_Z: RangeInclusive<i32> = -1024..=WorldPos::Z_MAX;
+
+/// Состояние движка, которое сохраняется вместе с миром.
+/// Без него после перезапуска тики пойдут с 1, и потоки `stream(tick, ..)`
+/// повторят случайность прошлого запуска.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineState {
+    /// Seed мира и основной поток RNG
+    pub rng: WorldRng,
+    /// Номер последнего выполненного тика
+    pub tick: u64,
+}
 
 pub struct Engine {
     // ECS
@@ -15,33 +42,77 @@ pub struct Engine {
     // Инфраструктура
     pub map: WorldMap,
     pub grid: SpatialGrid,
+    pub zones: ZoneRegistry,
+    pub materials: MaterialRegistry,
+    pub dimensions: DimensionRegistry,
+    pub instances: InstanceManager,
+    /// Границы мира: ходы за их пределы отклоняются
+    pub bounds: WorldBounds,
+    /// Seed мира и случайные потоки систем
+    pub rng: WorldRng,
+
+    // Системы тика (ИИ, движение, бой...)
+    pub scheduler: Scheduler,
 
-    // Маппинг GUID (наш ID) -> Entity (hecs ID)
-    // Это критически важно для производительности O(1)
-    entity_index: HashMap<ObjectGuid, Entity>,
+    // Окружение
+    pub fire: FireSystem,
+    pub environment: ChunkScheduler,
+
+    // Номер текущего тика
+    tick_count: u64,
 
     // Буфер структурных изменений (Spawn/Despawn)
     cmd_buffer: CommandBuffer,
-    entity_registry: EntityRegistry
+    entity_registry: EntityRegistry,
+
+    // События текущего тика (забираются через drain_events)
+    events: Vec<GameEvent>,
+    // Сколько событий из `events` окружение уже просмотрело (см. wake_changed_chunks).
+    // События между тиками (ignite) тоже попадают сюда, а не теряются.
+    env_seen: usize,
 }
 
 impl Default for Engine {
     fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Движок с заданным seed мира: одинаковый seed и ввод дают одинаковую симуляцию.
+    pub fn with_seed(seed: u64) -> Self {
+        let rng = WorldRng::new(seed);
+        let mut scheduler = Scheduler::new();
+        scheduler.add_builtin(Box::new(MovementSystem));
+
+        let mut dimensions = DimensionRegistry::new();
+        // Реестр пуст — резерв не может ни с чем пересечься
+        dimensions.reserve("overworld", *WORLD_Z.start(), *WORLD_Z.end());
+
         Self {
             world: World::new(),
             map: WorldMap::new(),
             grid: SpatialGrid::new(),
-            entity_index: HashMap::new(),
+            zones: ZoneRegistry::new(),
+            materials: MaterialRegistry::new(),
+            dimensions,
+            instances: InstanceManager::new(),
+            bounds: WorldBounds::ALL,
+            fire: FireSystem::new(),
+            environment: ChunkScheduler::new(),
+            rng,
+            scheduler,
+            tick_count: 0,
             cmd_buffer: CommandBuffer::new(),
             entity_registry: EntityRegistry::new(),
+            events: Vec::new(),
+            env_seen: 0,
         }
     }
-}
-
-impl Engine {
-    pub fn new() -> Self {
-        Self::default()
-    }
 
     /// Создание сущности (Фабрика)
     pub fn spawn_player(&mut self, guid: ObjectGuid, name: String, pos: WorldPos) {
@@ -52,7 +123,7 @@ impl Engine {
             Render { glyph: '@', color_rgb: 0x00FF00 },
             Stats { hp: 100, max_hp: 100, mana: 100, max_mana: 100 },
             // Важно: храним GUID внутри компонента тоже, для обратного поиска
-            cd_ecs::components::Controller { agent_id: "player".into() },
+            Controller { agent_id: "player".into() },
         ));
 
         // 2. Регистрируем в регистрах
@@ -62,49 +133,531 @@ impl Engine {
         info!("Spawned [{}] {} at {:?}", guid, name, pos);
     }
 
+    /// Удалить сущность из мира и всех индексов.
+    pub fn despawn(&mut self, guid: ObjectGuid) {
+        let Some(entity) = self.entity_registry.get_entity(guid) else { return };
+        if let Some(pos) = self.entity_pos(guid) {
+            self.grid.remove(guid, pos);
+        }
+        self.entity_registry.unregister(guid);
+        let _ = self.world.despawn(entity);
+        info!("Despawned [{}]", guid);
+    }
+
+    /// GUID сущности ECS (для снапшотов и сериализации).
+    pub fn guid_of(&self, entity: Entity) -> Option<ObjectGuid> {
+        self.entity_registry.get_guid(entity)
+    }
+
     /// Главный цикл симуляции (Tick)
     pub fn tick(&mut self, inputs: Vec<InputCmd>) {
+        self.tick_count += 1;
+
         // 1. Process Input (Cmd -> Component State/Intent)
         for cmd in inputs {
             self.handle_input(cmd);
         }
 
-        // 2. Logic Systems
-        // Передаем &mut self.world, чтобы системы могли итерироваться
-        // Но для сложных систем нам понадобится Context, пока сделаем просто функцию
-        systems::movement::run_movement(&mut self.world, &self.map, &mut self.grid, &self.entity_index);
+        // 2. Logic Systems (по стадиям, см. Scheduler)
+        // Сетка на время прогона уходит под RwLock: системы могут идти параллельно
+        let grid = RwLock::new(std::mem::take(&mut self.grid));
+        let res = Resources {
+            world: &self.world,
+            map: &self.map,
+            grid: &grid,
+            zones: &self.zones,
+            registry: &self.entity_registry,
+            rng: &self.rng,
+            tick: self.tick_count,
+        };
+        self.scheduler.run(&res, &mut self.events);
+        self.grid = grid.into_inner().unwrap_or_else(PoisonError::into_inner);
+        self.scheduler.flush_commands(&mut self.world);
 
-        // 3. Apply Structural Changes (если системы просили удалить/создать сущности)
+        // 3. Environment
+        let mut fire_rng = self.rng.stream(self.tick_count, "fire");
+        self.fire.step(&self.map, &self.materials, &mut fire_rng, &mut self.events);
+        self.fire.damage_entities(&mut self.world, &self.grid, &self.entity_registry, &mut self.events);
+        self.run_environment();
+        self.update_instances();
+
+        // 4. Apply Structural Changes (если системы просили удалить/создать сущности)
         self.cmd_buffer.run_on(&mut self.world);
     }
 
+    pub fn current_tick(&self) -> u64 {
+        self.tick_count
+    }
+
+    /// Снимок состояния для сохранения.
+    pub fn save_state(&self) -> EngineState {
+        EngineState { rng: self.rng.clone(), tick: self.tick_count }
+    }
+
+    /// Продолжить с сохраненного состояния: RNG и счетчик тиков.
+    pub fn restore_state(&mut self, state: EngineState) {
+        self.rng = state.rng;
+        self.tick_count = state.tick;
+    }
+
+    /// Движок, продолжающий сохраненный мир.
+    pub fn from_state(state: EngineState) -> Self {
+        let mut engine = Self::with_seed(state.rng.seed());
+        engine.restore_state(state);
+        engine
+    }
+
+    /// Подключить систему тика.
+    pub fn add_system(&mut self, system: Box<dyn System>) -> Result<(), ScheduleError> {
+        self.scheduler.add(system)
+    }
+
+    /// Подключить систему окружения (жидкости, растения, газ).
+    pub fn register_environment(&mut self, system: Box<dyn EnvironmentSystem>) {
+        self.environment.register(system);
+    }
+
+    /// Будит чанки рядом с игроками и измененные с прошлого прогона, затем раздает случайные тики.
+    fn run_environment(&mut self) {
+        let tick = self.tick_count;
+        self.wake_changed_chunks();
+
+        let players: Vec<WorldPos> = self
+            .world
+            .query::<(&Position, &Controller)>()
+            .iter()
+            .map(|(_, (pos, _))| pos.0)
+            .collect();
+        self.environment.update_active(players, tick);
+
+        let mut rng = self.rng.stream(tick, "environment");
+        self.environment.run(&self.map, &self.materials, &mut self.events, &mut rng, tick);
+    }
+
+    // --- Instances ---
+
+    /// Создать копию подземелья для группы: выделить измерение, скопировать карту шаблона
+    /// и заселить его. `alloc_guid` выдает GUID для монстров шаблона.
+    pub fn create_instance(
+        &mut self,
+        template: &InstanceTemplate,
+        party: Vec<ObjectGuid>,
+        mut alloc_guid: impl FnMut() -> ObjectGuid,
+    ) -> Option<InstanceId> {
+        let dimension = self.dimensions.allocate(template.name.clone(), template.floors)?;
+        let z_min = self.dimensions.get(dimension)?.z_min;
+        let id = self.instances.insert(template, dimension, z_min, party, self.tick_count);
+        let instance = self.instances.get(id)?.clone();
+
+        for (chunk_key, chunk) in &template.chunks {
+            self.map.put_chunk(instance.to_world(*chunk_key), chunk.clone());
+        }
+
+        let mut spawned = Vec::with_capacity(template.spawns.len());
+        for spawn in &template.spawns {
+            let guid = alloc_guid();
+            let pos = instance.to_world(spawn.pos);
+            let entity = self.world.spawn((
+                Position(pos),
+                Name(spawn.name.clone()),
+                Render { glyph: spawn.glyph, color_rgb: 0xFF0000 },
+                Stats { hp: spawn.hp, max_hp: spawn.hp, mana: 0, max_mana: 0 },
+            ));
+            self.entity_registry.register(guid, entity);
+            self.grid.insert(guid, pos);
+            spawned.push(guid);
+        }
+        self.instances.get_mut(id)?.spawned = spawned;
+
+        info!("Created instance {:?} of '{}' at z {:?}", id, template.name, instance.z_range());
+        Some(id)
+    }
+
+    /// Перенести участника группы на вход инстанса.
+    pub fn enter_instance(&mut self, id: InstanceId, guid: ObjectGuid) -> bool {
+        let from = self.entity_pos(guid);
+        let Some(instance) = self.instances.get_mut(id) else { return false };
+        if !instance.is_member(guid) {
+            warn!("Entity {} is not in the party of instance {:?}", guid, id);
+            return false;
+        }
+        if let Some(from) = from {
+            instance.set_return_point(guid, from);
+        }
+        let entry = instance.entry();
+        self.teleport(guid, entry)
+    }
+
+    /// Вывести сущность из инстанса в `to`.
+    pub fn leave_instance(&mut self, guid: ObjectGuid, to: WorldPos) -> bool {
+        let inside = self.entity_pos(guid).is_some_and(|pos| self.instances.instance_at(pos).is_some());
+        inside && self.teleport(guid, to)
+    }
+
+    /// Удалить инстанс: его сущности, карту и измерение.
+    /// Игроков внутри сначала выводим туда, откуда они вошли (как `leave_instance`),
+    /// а не удаляем вместе с монстрами.
+    pub fn destroy_instance(&mut self, id: InstanceId) {
+        let Some(instance) = self.instances.remove(id) else { return };
+
+        let players: Vec<ObjectGuid> = self
+            .world
+            .query::<(&Position, &Controller)>()
+            .iter()
+            .filter(|(_, (pos, _))| instance.contains(pos.0))
+            .filter_map(|(entity, _)| self.entity_registry.get_guid(entity))
+            .collect();
+        for guid in players {
+            let to = instance.return_point(guid).unwrap_or(SPAWN_POS);
+            self.teleport(guid, to);
+        }
+
+        let inside: Vec<(Entity, WorldPos)> = self
+            .world
+            .query::<&Position>()
+            .iter()
+            .filter(|(_, pos)| instance.contains(pos.0))
+            .map(|(entity, pos)| (entity, pos.0))
+            .collect();
+        for (entity, pos) in inside {
+            if let Some(guid) = self.entity_registry.get_guid(entity) {
+                self.grid.remove(guid, pos);
+                self.entity_registry.unregister(guid);
+            }
+            let _ = self.world.despawn(entity);
+        }
+
+        self.map.clear_levels(instance.z_range());
+        self.dimensions.release(instance.dimension);
+        info!("Destroyed instance {:?} of '{}'", id, instance.template);
+    }
+
+    fn update_instances(&mut self) {
+        if self.instances.is_empty() {
+            return;
+        }
+        let players: Vec<WorldPos> = self
+            .world
+            .query::<(&Position, &Controller)>()
+            .iter()
+            .map(|(_, (pos, _))| pos.0)
+            .collect();
+        for id in self.instances.update(players, self.tick_count) {
+            self.destroy_instance(id);
+        }
+    }
+
+    /// Мгновенно переместить сущность (портал, вход в инстанс).
+    pub(crate) fn teleport(&mut self, guid: ObjectGuid, to: WorldPos) -> bool {
+        let Some(entity) = self.entity_registry.get_entity(guid) else { return false };
+        let Ok(mut pos) = self.world.get::<&mut Position>(entity) else { return false };
+        let from = pos.0;
+        pos.0 = to;
+        drop(pos);
+
+        self.grid.move_entity(guid, from, to);
+        self.events.push(GameEvent::Teleported { entity_guid: guid, from, to });
+        let transition = self.zones.transition(from, to);
+        for zone in transition.left {
+            self.events.push(GameEvent::ZoneLeft { entity_guid: guid, zone });
+        }
+        for zone in transition.entered {
+            self.events.push(GameEvent::ZoneEntered { entity_guid: guid, zone });
+        }
+        true
+    }
+
+    /// Поджечь тайл (заклинание, факел, лава).
+    pub fn ignite(&mut self, pos: WorldPos) -> bool {
+        let ignited = self.fire.ignite(&self.map, &self.materials, pos);
+        if ignited {
+            self.events.push(GameEvent::FireStarted { pos });
+        }
+        ignited
+    }
+
+    /// Будит чанки по событиям, которые окружение еще не видело.
+    fn wake_changed_chunks(&mut self) {
+        let tick = self.tick_count;
+        for event in &self.events[self.env_seen..] {
+            if let GameEvent::TileChanged { pos, .. } | GameEvent::FireStarted { pos } = event {
+                self.environment.mark_changed(pos.chunk_key(), tick);
+            }
+        }
+        self.env_seen = self.events.len();
+    }
+
+    /// Забрать события, накопленные за тик(и).
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, GameEvent> {
+        // Перед тем как отдать события, окружение должно их увидеть
+        self.wake_changed_chunks();
+        self.env_seen = 0;
+        self.events.drain(..)
+    }
+
     fn handle_input(&mut self, cmd: InputCmd) {
         match cmd {
             InputCmd::Move { entity_guid, target } => {
-                // Находим hecs::Entity по GUID
-                if let Some(&entity) = self.entity_index.get(&entity_guid) {
-                    // Добавляем/Обновляем компонент "TargetPosition" или просто телепортируем пока для теста
-                    // В реальной игре тут мы бы добавили компонент IntentMove
-
-                    // ХАК для теста: просто меняем позицию, если нет стены
-                    // В нормальной системе это сделает movement_system
-                    if !self.map.is_solid(target) {
-                        // Получаем доступ к позиции
-                        if let Ok(mut pos) = self.world.get::<&mut Position>(entity) {
-                            let old_pos = pos.0;
-                            pos.0 = target;
-                            // Обновляем Grid
-                            self.grid.move_entity(entity_guid, old_pos, target);
-                            info!("Entity {} moved to {:?}", entity_guid, target);
-                        }
-                    } else {
-                        warn!("Entity {} hit a wall at {:?}", entity_guid, target);
-                    }
-                } else {
+                if let Err(e) = self.bounds.check(target) {
+                    warn!("Entity {} move rejected: {}", entity_guid, e);
+                    return;
+                }
+                // Ввод только записывает намерение — правила шага в MovementSystem
+                let Some(entity) = self.entity_registry.get_entity(entity_guid) else {
                     warn!("Input for unknown entity: {:?}", entity_guid);
+                    return;
+                };
+                let _ = self.world.insert_one(entity, MoveIntent { target });
+            }
+            InputCmd::Join { entity_guid, name } => {
+                if let Err(e) = entity_guid.ensure_kind(ObjectKind::Player) {
+                    warn!("Join rejected: {}", e);
+                    return;
                 }
+                if self.entity_registry.get_entity(entity_guid).is_some() {
+                    warn!("Entity {} already joined", entity_guid);
+                    return;
+                }
+                self.spawn_player(entity_guid, name, SPAWN_POS);
             }
+            InputCmd::Leave { entity_guid } => self.despawn(entity_guid),
+            InputCmd::Interact { actor, target } => self.handle_interact(actor, target),
+            InputCmd::DamageTile { actor, target, amount } => self.handle_damage_tile(actor, target, amount),
             _ => {} // Пока игнорируем остальное
         }
     }
-}
\ No newline at end of file
+
+    /// Переключение состояния интерактивного тайла (дверь, рычаг).
+    fn handle_interact(&mut self, actor: ObjectGuid, target: WorldPos) {
+        let Some(actor_pos) = self.entity_pos(actor) else {
+            warn!("Input for unknown entity: {:?}", actor);
+            return;
+        };
+
+        if !Self::can_reach(actor_pos, target) {
+            warn!("Entity {} can't reach {:?}", actor, target);
+            return;
+        }
+
+        let tile = self.map.get_tile(target);
+        let Some(next) = self.materials.get(tile.material).and_then(|def| def.next_state(tile)) else {
+            warn!("Entity {} tried to interact with static tile at {:?}", actor, target);
+            return;
+        };
+
+        // Нельзя закрыть дверь, если в проеме кто-то стоит
+        if !next.is_walkable() && self.is_occupied(target) {
+            warn!("Entity {} can't close {:?}: tile is occupied", actor, target);
+            return;
+        }
+
+        self.map.set_tile(target, next);
+        self.events.push(GameEvent::TileChanged { pos: target, tile: next });
+        info!("Entity {} switched tile at {:?} to variant {}", actor, target, next.variant);
+    }
+
+    /// Копание / разрушение тайла.
+    fn handle_damage_tile(&mut self, actor: ObjectGuid, target: WorldPos, amount: u16) {
+        let Some(actor_pos) = self.entity_pos(actor) else {
+            warn!("Input for unknown entity: {:?}", actor);
+            return;
+        };
+
+        if !Self::can_reach(actor_pos, target) {
+            warn!("Entity {} can't reach {:?}", actor, target);
+            return;
+        }
+
+        match self.map.damage_tile(target, amount, &self.materials) {
+            DamageOutcome::Immune => {
+                warn!("Entity {} can't damage tile at {:?}", actor, target);
+            }
+            DamageOutcome::Damaged { damage, hardness } => {
+                self.events.push(GameEvent::TileDamaged { pos: target, damage, hardness });
+            }
+            DamageOutcome::Destroyed { rubble } => {
+                self.events.push(GameEvent::TileChanged { pos: target, tile: rubble });
+                info!("Entity {} destroyed tile at {:?}", actor, target);
+            }
+        }
+    }
+
+    // --- Helpers ---
+
+    /// Дотянуться можно только до соседнего тайла (или своего) на том же уровне.
+    fn can_reach(from: WorldPos, target: WorldPos) -> bool {
+        from.z() == target.z() && from.chebyshev_distance(target) <= 1
+    }
+
+    pub(crate) fn entity_pos(&self, guid: ObjectGuid) -> Option<WorldPos> {
+        let entity = self.entity_registry.get_entity(guid)?;
+        self.world.get::<&Position>(entity).ok().map(|p| p.0)
+    }
+
+    /// Стоит ли на тайле хоть одна сущность.
+    fn is_occupied(&self, pos: WorldPos) -> bool {
+        self.grid.entities_at(pos).next().is_some()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cd_map::{MaterialDef, Tile, TileFlags, TileState};
+
+    const DOOR: u16 = 10;
+
+    fn engine_with_door() -> (Engine, MaterialDef) {
+        let door = MaterialDef::with_states(DOOR, "Wooden Door", vec![
+            TileState::new("closed", TileFlags::SOLID | TileFlags::OPAQUE),
+            TileState::new("open", TileFlags::WALKABLE),
+        ]);
+        let mut engine = Engine::new();
+        engine.materials.register(door.clone());
+        (engine, door)
+    }
+
+    fn interact(engine: &mut Engine, actor: ObjectGuid, target: WorldPos) -> Vec<GameEvent> {
+        engine.tick(vec![InputCmd::Interact { actor, target }]);
+        engine.drain_events().collect()
+    }
+
+    #[test]
+    fn test_interact_toggles_door() {
+        let (mut engine, door) = engine_with_door();
+        let closed = door.tile_by_name("closed").unwrap();
+        let open = door.tile_by_name("open").unwrap();
+        let (a, b) = (ObjectGuid::player(0, 0, 1), ObjectGuid::player(0, 0, 2));
+        engine.spawn_player(a, "A".into(), WorldPos::new(0, 0, 0));
+
+        let near = WorldPos::new(1, 1, 0);
+        let far = WorldPos::new(2, 0, 0);
+        engine.map.set_tile(near, closed);
+        engine.map.set_tile(far, closed);
+
+        // Не дотянуться: ни события, ни изменения
+        assert!(interact(&mut engine, a, far).is_empty());
+        assert!(interact(&mut engine, a, WorldPos::new(1, 0, 1)).is_empty()); // Другой этаж
+        assert_eq!(engine.map.get_tile(far), closed);
+
+        // Статичный тайл не переключается
+        assert!(interact(&mut engine, a, WorldPos::new(0, 1, 0)).is_empty());
+
+        assert_eq!(interact(&mut engine, a, near), vec![GameEvent::TileChanged { pos: near, tile: open }]);
+        assert_eq!(engine.map.get_tile(near), open);
+        assert!(!engine.map.is_solid_fast(near));
+
+        // В проеме стоит b — закрыть нельзя
+        engine.spawn_player(b, "B".into(), near);
+        assert!(interact(&mut engine, a, near).is_empty());
+        assert_eq!(engine.map.get_tile(near), open);
+
+        engine.despawn(b);
+        assert_eq!(interact(&mut engine, a, near), vec![GameEvent::TileChanged { pos: near, tile: closed }]);
+        assert!(engine.map.is_solid_fast(near));
+    }
+
+    #[test]
+    fn test_damage_tile_breaks_to_rubble() {
+        const ROCK: Tile = Tile { material: 2, flags: TileFlags::SOLID, variant: 0 };
+        const RUBBLE: Tile = Tile { material: 3, flags: TileFlags::WALKABLE, variant: 0 };
+        // Трещины: тот же материал, другой вариант — и тоже разрушаемые
+        const CRACKED: Tile = Tile { material: 4, flags: TileFlags::SOLID, variant: 0 };
+        const CRACKED_RUBBLE: Tile = Tile { material: 4, flags: TileFlags::WALKABLE, variant: 1 };
+
+        let mut engine = Engine::new();
+        engine.materials.register(MaterialDef::simple(2, "Rock", TileFlags::SOLID).destructible(10, RUBBLE));
+        engine.materials.register(MaterialDef::simple(3, "Rubble", TileFlags::WALKABLE));
+        engine.materials.register(MaterialDef::simple(4, "Cracked", TileFlags::SOLID).destructible(10, CRACKED_RUBBLE));
+
+        let a = ObjectGuid::player(0, 0, 1);
+        engine.spawn_player(a, "A".into(), WorldPos::new(0, 0, 0));
+        let rock = WorldPos::new(1, 0, 0);
+        let far = WorldPos::new(2, 0, 0);
+        engine.map.set_tile(rock, ROCK);
+        engine.map.set_tile(far, ROCK);
+
+        let hit = |engine: &mut Engine, target, amount| -> Vec<GameEvent> {
+            engine.tick(vec![InputCmd::DamageTile { actor: a, target, amount }]);
+            engine.drain_events().collect()
+        };
+
+        assert!(hit(&mut engine, far, 100).is_empty()); // Не дотянуться
+        assert_eq!(engine.map.get_tile(far), ROCK);
+
+        // Урон копится между тиками
+        assert_eq!(hit(&mut engine, rock, 4), vec![GameEvent::TileDamaged { pos: rock, damage: 4, hardness: 10 }]);
+        assert_eq!(hit(&mut engine, rock, 4), vec![GameEvent::TileDamaged { pos: rock, damage: 8, hardness: 10 }]);
+        assert_eq!(hit(&mut engine, rock, 4), vec![GameEvent::TileChanged { pos: rock, tile: RUBBLE }]);
+        assert_eq!(engine.map.get_tile(rock), RUBBLE);
+        assert!(!engine.map.is_solid_fast(rock));
+        assert!(hit(&mut engine, rock, 4).is_empty()); // Обломки неразрушаемы
+
+        // Обломки того же материала начинают с нуля урона
+        engine.map.set_tile(rock, CRACKED);
+        assert_eq!(hit(&mut engine, rock, 12), vec![GameEvent::TileChanged { pos: rock, tile: CRACKED_RUBBLE }]);
+        assert_eq!(engine.map.get_tile_data(rock), None);
+        engine.map.set_tile(rock, CRACKED);
+        assert_eq!(hit(&mut engine, rock, 4), vec![GameEvent::TileDamaged { pos: rock, damage: 4, hardness: 10 }]);
+    }
+
+    #[test]
+    fn test_ignite_between_ticks_wakes_chunk() {
+        const GRASS: Tile = Tile { material: 1, flags: TileFlags::WALKABLE, variant: 0 };
+        const ASH: Tile = Tile { material: 2, flags: TileFlags::WALKABLE, variant: 0 };
+
+        let mut engine = Engine::new();
+        engine.materials.register(MaterialDef::simple(1, "Grass", TileFlags::WALKABLE).flammable(1, 50, ASH));
+        engine.materials.register(MaterialDef::simple(2, "Ash", TileFlags::WALKABLE));
+
+        // Без игроков: разбудить чанк может только изменение
+        let pos = WorldPos::new(500, 500, 0);
+        engine.map.set_tile(pos, GRASS);
+        engine.tick(vec![]);
+
+        assert!(engine.ignite(pos));
+        engine.tick(vec![]);
+        assert!(engine.environment.is_active(pos.chunk_key()));
+
+        // И если события успели забрать до тика
+        let other = WorldPos::new(-500, 500, 0);
+        engine.map.set_tile(other, GRASS);
+        assert!(engine.ignite(other));
+        let _ = engine.drain_events().count();
+        engine.tick(vec![]);
+        assert!(engine.environment.is_active(other.chunk_key()));
+    }
+
+    #[test]
+    fn test_restored_engine_does_not_replay_randomness() {
+        let mut engine = Engine::with_seed(99);
+        for _ in 0..5 {
+            engine.tick(vec![]);
+        }
+        let first_run: Vec<u64> = (0..3).map(|_| engine.rng.main().next_u64()).collect();
+        let saved = serde_json::to_string(&engine.save_state()).unwrap();
+
+        // Продолжение в том же процессе — эталон
+        let expected_main = engine.rng.main().next_u64();
+        engine.tick(vec![]);
+        let expected_fire = engine.rng.stream(engine.current_tick(), "fire").next_u64();
+
+        let mut restored = Engine::from_state(serde_json::from_str(&saved).unwrap());
+        assert_eq!(restored.current_tick(), 5);
+        assert_eq!(restored.rng.seed(), 99);
+        let next = restored.rng.main().next_u64();
+        assert_eq!(next, expected_main);
+        assert!(!first_run.contains(&next));
+
+        restored.tick(vec![]);
+        assert_eq!(restored.current_tick(), 6);
+        let fire = restored.rng.stream(restored.current_tick(), "fire").next_u64();
+        assert_eq!(fire, expected_fire);
+        assert_ne!(fire, restored.rng.stream(1, "fire").next_u64());
+    }
+
+    #[test]
+    fn test_builtin_systems_are_scheduled() {
+        let engine = Engine::with_seed(1);
+        assert!(engine.scheduler.order().any(|name| name == "movement"));
+    }
+}
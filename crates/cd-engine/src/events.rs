@@ -0,0 +1,75 @@
+use cd_core::{ObjectGuid, WorldPos};
+use cd_map::{Tile, ZoneId};
+
+/// События, которые движок порождает за тик.
+/// Их забирает сеть (рассылка клиентам) и скрипты (триггеры).
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// Сущность вошла в зону
+    ZoneEntered {
+        entity_guid: ObjectGuid,
+        zone: ZoneId,
+    },
+    /// Сущность покинула зону
+    ZoneLeft {
+        entity_guid: ObjectGuid,
+        zone: ZoneId,
+    },
+    /// Сущность сделала шаг (исполнено `MoveIntent`)
+    EntityMoved {
+        entity_guid: ObjectGuid,
+        from: WorldPos,
+        to: WorldPos,
+    },
+    /// Шаг отклонен системой движения
+    MoveBlocked {
+        entity_guid: ObjectGuid,
+        target: WorldPos,
+        reason: MoveBlockReason,
+    },
+    /// Сущность мгновенно перенесена (портал, вход/выход из инстанса)
+    Teleported {
+        entity_guid: ObjectGuid,
+        from: WorldPos,
+        to: WorldPos,
+    },
+    /// Тайл карты изменился (клиентам и кэшам путей нужно обновиться)
+    TileChanged {
+        pos: WorldPos,
+        tile: Tile,
+    },
+    /// Тайл получил урон, но еще держится
+    TileDamaged {
+        pos: WorldPos,
+        damage: u16,
+        hardness: u16,
+    },
+    /// Тайл загорелся (клиенту — показать огонь и свет)
+    FireStarted {
+        pos: WorldPos,
+    },
+    /// Тайл догорел
+    FireEnded {
+        pos: WorldPos,
+    },
+    /// Сущность получила урон от окружения
+    EntityDamaged {
+        entity_guid: ObjectGuid,
+        amount: i32,
+    },
+}
+
+/// Почему шаг не удался.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveBlockReason {
+    /// Цель не соседняя клетка
+    TooFar,
+    /// В цели стена
+    Solid,
+    /// Диагональ сквозь стык двух стен
+    CornerCut,
+    /// Смена этажа не по лестнице
+    NoStairs,
+    /// Клетка занята другой сущностью
+    Occupied,
+}
@@ -4,11 +4,31 @@ use cd_core::{ObjectGuid, WorldPos};
 /// Это "чистые" данные.
 #[derive(Debug, Clone)]
 pub enum InputCmd {
+    /// Игрок вошел в игру (GUID выдан сетью через `GuidAllocator`)
+    Join {
+        entity_guid: ObjectGuid,
+        name: String,
+    },
+    /// Игрок отключился
+    Leave {
+        entity_guid: ObjectGuid,
+    },
     /// Игрок хочет переместиться
     Move {
         entity_guid: ObjectGuid,
         target: WorldPos,
     },
+    /// Игрок взаимодействует с тайлом (открыть дверь, дернуть рычаг)
+    Interact {
+        actor: ObjectGuid,
+        target: WorldPos,
+    },
+    /// Игрок копает / бьет тайл (кирка, взрыв)
+    DamageTile {
+        actor: ObjectGuid,
+        target: WorldPos,
+        amount: u16,
+    },
     /// Игрок хочет скастовать спелл (заготовка на будущее)
     Cast {
         caster: ObjectGuid,
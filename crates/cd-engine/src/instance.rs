@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use cd_core::{ObjectGuid, WorldPos};
+use cd_map::{Chunk, DimensionId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InstanceId(pub u32);
+
+/// Сущность, которую шаблон расставляет при создании копии.
+#[derive(Debug, Clone)]
+pub struct InstanceSpawn {
+    /// Позиция в координатах шаблона
+    pub pos: WorldPos,
+    pub name: String,
+    pub glyph: char,
+    pub hp: i32,
+}
+
+/// Шаблон подземелья. Координаты локальные: z = 0 — нижний этаж копии,
+/// z = floors - 1 — верхний. При создании копии z сдвигается в выделенное измерение.
+#[derive(Debug, Clone)]
+pub struct InstanceTemplate {
+    pub name: String,
+    pub floors: i32,
+    /// (chunk_key, chunk) в локальных координатах
+    pub chunks: Vec<(WorldPos, Chunk)>,
+    /// Куда попадает вошедший
+    pub entry: WorldPos,
+    pub spawns: Vec<InstanceSpawn>,
+}
+
+/// Живая копия шаблона для одной группы.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub id: InstanceId,
+    pub template: String,
+    pub dimension: DimensionId,
+    pub z_min: i32,
+    pub z_max: i32,
+    /// Кому разрешен вход
+    pub party: Vec<ObjectGuid>,
+    /// Сущности, созданные при заселении (монстры, NPC)
+    pub spawned: Vec<ObjectGuid>,
+    entry: WorldPos,
+    // Откуда вошел каждый участник: туда его вернут при удалении копии
+    returns: HashMap<ObjectGuid, WorldPos>,
+    // Тик, с которого внутри никого нет
+    idle_since: Option<u64>,
+}
+
+impl Instance {
+    pub fn z_range(&self) -> RangeInclusive<i32> {
+        self.z_min..=self.z_max
+    }
+
+    pub fn contains(&self, pos: WorldPos) -> bool {
+        self.z_range().contains(&pos.z())
+    }
+
+    /// Перевод координат шаблона в мировые.
+    pub fn to_world(&self, local: WorldPos) -> WorldPos {
+        let (x, y, z) = local.xyz();
+        WorldPos::new(x, y, self.z_min + z)
+    }
+
+    pub fn entry(&self) -> WorldPos {
+        self.entry
+    }
+
+    pub fn is_member(&self, guid: ObjectGuid) -> bool {
+        self.party.contains(&guid)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle_since.is_some()
+    }
+
+    /// Где участник был до входа.
+    pub fn return_point(&self, guid: ObjectGuid) -> Option<WorldPos> {
+        self.returns.get(&guid).copied()
+    }
+
+    /// Запомнить точку возврата. Повторный вход изнутри копии ее не перетирает.
+    pub fn set_return_point(&mut self, guid: ObjectGuid, pos: WorldPos) {
+        if !self.contains(pos) {
+            self.returns.insert(guid, pos);
+        }
+    }
+}
+
+/// Реестр живых инстансов и их жизненный цикл.
+/// Сама карта и сущности принадлежат движку — менеджер только ведет учет и решает,
+/// когда копию пора удалить.
+#[derive(Debug)]
+pub struct InstanceManager {
+    instances: HashMap<InstanceId, Instance>,
+    next_id: u32,
+    /// Сколько тиков пустой инстанс живет до удаления.
+    pub idle_timeout: u64,
+}
+
+impl Default for InstanceManager {
+    fn default() -> Self {
+        Self { instances: HashMap::new(), next_id: 1, idle_timeout: 600 }
+    }
+}
+
+impl InstanceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Завести запись о копии в уже выделенном диапазоне z.
+    pub fn insert(
+        &mut self,
+        template: &InstanceTemplate,
+        dimension: DimensionId,
+        z_min: i32,
+        party: Vec<ObjectGuid>,
+        tick: u64,
+    ) -> InstanceId {
+        let id = InstanceId(self.next_id);
+        self.next_id += 1;
+
+        let mut instance = Instance {
+            id,
+            template: template.name.clone(),
+            dimension,
+            z_min,
+            z_max: z_min + template.floors - 1,
+            party,
+            spawned: Vec::new(),
+            entry: template.entry,
+            returns: HashMap::new(),
+            // Пустой с момента создания: если группа так и не вошла, копия истечет
+            idle_since: Some(tick),
+        };
+        instance.entry = instance.to_world(template.entry);
+        self.instances.insert(id, instance);
+        id
+    }
+
+    pub fn get(&self, id: InstanceId) -> Option<&Instance> {
+        self.instances.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: InstanceId) -> Option<&mut Instance> {
+        self.instances.get_mut(&id)
+    }
+
+    pub fn remove(&mut self, id: InstanceId) -> Option<Instance> {
+        self.instances.remove(&id)
+    }
+
+    /// Инстанс, в котором лежит позиция.
+    pub fn instance_at(&self, pos: WorldPos) -> Option<&Instance> {
+        self.instances.values().find(|i| i.contains(pos))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Instance> {
+        self.instances.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Обновить занятость по позициям игроков.
+    /// Возвращает инстансы, простоявшие пустыми дольше `idle_timeout` (по возрастанию id).
+    pub fn update(&mut self, players: impl IntoIterator<Item = WorldPos>, tick: u64) -> Vec<InstanceId> {
+        let mut occupied = HashSet::new();
+        for pos in players {
+            if let Some(instance) = self.instance_at(pos) {
+                occupied.insert(instance.id);
+            }
+        }
+
+        let mut expired = Vec::new();
+        for instance in self.instances.values_mut() {
+            if occupied.contains(&instance.id) {
+                instance.idle_since = None;
+                continue;
+            }
+            let since = *instance.idle_since.get_or_insert(tick);
+            if tick.saturating_sub(since) >= self.idle_timeout {
+                expired.push(instance.id);
+            }
+        }
+        expired.sort_unstable();
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{SPAWN_POS, WORLD_Z};
+    use crate::events::GameEvent;
+    use crate::Engine;
+    use cd_map::{Tile, TileFlags};
+
+    const FLOOR: Tile = Tile { material: 1, flags: TileFlags::WALKABLE, variant: 0 };
+
+    fn template() -> InstanceTemplate {
+        let mut chunk = Chunk::new();
+        for ly in 0..16 {
+            for lx in 0..16 {
+                chunk.set_tile(lx, ly, FLOOR);
+            }
+        }
+        InstanceTemplate {
+            name: "crypt".into(),
+            floors: 2,
+            chunks: vec![(WorldPos::new(0, 0, 0), chunk.clone()), (WorldPos::new(0, 0, 1), chunk)],
+            entry: WorldPos::new(2, 2, 1),
+            spawns: vec![InstanceSpawn { pos: WorldPos::new(8, 8, 0), name: "Ghoul".into(), glyph: 'g', hp: 20 }],
+        }
+    }
+
+    fn guid(index: u32) -> ObjectGuid {
+        ObjectGuid::player(0, 0, index)
+    }
+
+    #[test]
+    fn test_party_copies_are_isolated() {
+        let mut engine = Engine::new();
+        let (a, b) = (guid(1), guid(2));
+        engine.spawn_player(a, "A".into(), WorldPos::new(0, 0, 0));
+        engine.spawn_player(b, "B".into(), WorldPos::new(0, 0, 0));
+
+        let mut next = 100;
+        let mut alloc = || {
+            next += 1;
+            ObjectGuid::monster(0, 0, next)
+        };
+        let first = engine.create_instance(&template(), vec![a], &mut alloc).unwrap();
+        let second = engine.create_instance(&template(), vec![b], &mut alloc).unwrap();
+
+        assert!(engine.enter_instance(first, a));
+        assert!(!engine.enter_instance(first, b)); // Чужая группа
+        assert!(engine.enter_instance(second, b));
+
+        let (e1, e2) = (engine.instances.get(first).unwrap().entry(), engine.instances.get(second).unwrap().entry());
+        assert_eq!((e1.x(), e1.y()), (e2.x(), e2.y()));
+        assert_ne!(e1.z(), e2.z());
+        assert_eq!(engine.grid.query_bucket(e1), &[a]);
+        assert_eq!(engine.grid.query_bucket(e2), &[b]);
+
+        // Монстры заселены в каждую копию отдельно
+        let ghoul = engine.instances.get(first).unwrap().to_world(WorldPos::new(8, 8, 0));
+        assert_eq!(engine.grid.query_bucket(ghoul).len(), 1);
+    }
+
+    #[test]
+    fn test_idle_instance_is_torn_down() {
+        let mut engine = Engine::new();
+        engine.instances.idle_timeout = 3;
+        let a = guid(1);
+        engine.spawn_player(a, "A".into(), WorldPos::new(0, 0, 0));
+
+        let mut next = 100;
+        let id = engine
+            .create_instance(&template(), vec![a], || {
+                next += 1;
+                ObjectGuid::monster(0, 0, next)
+            })
+            .unwrap();
+        let entry = engine.instances.get(id).unwrap().entry();
+        engine.enter_instance(id, a);
+
+        // Пока внутри игрок — живет сколько угодно
+        for _ in 0..10 {
+            engine.tick(vec![]);
+        }
+        assert!(engine.instances.get(id).is_some());
+
+        // Игрок вышел — через idle_timeout тиков копия удалена вместе с картой и монстрами
+        assert!(engine.leave_instance(a, WorldPos::new(0, 0, 0)));
+        for _ in 0..4 {
+            engine.tick(vec![]);
+        }
+        assert!(engine.instances.get(id).is_none());
+        assert_eq!(engine.map.get_tile(entry), Tile::default());
+        assert_eq!(engine.dimensions.len(), 1); // Остался только overworld
+        assert_eq!(engine.world.len(), 1);
+    }
+
+    #[test]
+    fn test_destroying_occupied_instance_evicts_players() {
+        let mut engine = Engine::new();
+        let (a, b) = (guid(1), guid(2));
+        let home = WorldPos::new(30, -4, 0);
+        engine.spawn_player(a, "A".into(), home);
+        engine.spawn_player(b, "B".into(), WorldPos::new(0, 0, 0));
+
+        let mut next = 100;
+        let id = engine
+            .create_instance(&template(), vec![a, b], || {
+                next += 1;
+                ObjectGuid::monster(0, 0, next)
+            })
+            .unwrap();
+        let entry = engine.instances.get(id).unwrap().entry();
+        assert!(engine.enter_instance(id, a));
+        assert!(engine.enter_instance(id, a)); // Повторный вход не перетирает точку возврата
+        // b попал внутрь мимо enter_instance — точки возврата нет
+        engine.teleport(b, entry);
+        engine.drain_events().for_each(drop);
+
+        engine.destroy_instance(id);
+
+        // Игроки живы и стоят снаружи, монстры удалены
+        assert_eq!(engine.entity_pos(a), Some(home));
+        assert_eq!(engine.entity_pos(b), Some(SPAWN_POS));
+        assert_eq!(engine.grid.query_bucket(home), &[a]);
+        assert_eq!(engine.world.len(), 2);
+        let events: Vec<_> = engine.drain_events().collect();
+        assert!(events.contains(&GameEvent::Teleported { entity_guid: a, from: entry, to: home }));
+        assert!(events.contains(&GameEvent::Teleported { entity_guid: b, from: entry, to: SPAWN_POS }));
+    }
+
+    #[test]
+    fn test_instances_never_overlap_the_world() {
+        let mut engine = Engine::new();
+        let a = guid(1);
+        engine.spawn_player(a, "A".into(), WorldPos::new(0, 0, 0));
+        let surface = WorldPos::new(3, 3, 0);
+        let dungeon = WorldPos::new(3, 3, -5);
+        engine.map.set_tile(surface, FLOOR);
+        engine.map.set_tile(dungeon, FLOOR);
+
+        // Выделяем, пока пул не кончится: до z = 0 и мира дело дойти не должно
+        let mut next = 1000;
+        let mut ids = Vec::new();
+        while let Some(id) = engine.create_instance(&template(), vec![a], || {
+            next += 1;
+            ObjectGuid::monster(0, 0, next)
+        }) {
+            let instance = engine.instances.get(id).unwrap();
+            assert!(instance.z_max < *WORLD_Z.start(), "instance at {:?}", instance.z_range());
+            ids.push(id);
+        }
+        assert!(!ids.is_empty());
+
+        for id in ids {
+            engine.destroy_instance(id);
+        }
+        assert_eq!(engine.map.get_tile(surface), FLOOR);
+        assert_eq!(engine.map.get_tile(dungeon), FLOOR);
+        assert_eq!(engine.entity_pos(a), Some(WorldPos::new(0, 0, 0)));
+        assert_eq!(engine.dimensions.len(), 1);
+    }
+}
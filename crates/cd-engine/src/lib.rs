@@ -1,7 +1,16 @@
 pub mod input;
 pub mod engine;
 pub mod systems;
+pub mod events;
+pub mod chunk_scheduler;
+pub mod instance;
+pub mod rng;
+pub mod schedule;
 mod registry;
 
-pub use engine::Engine;
-pub use input::InputCmd;
\ No newline at end of file
+pub use engine::{Engine, EngineState, SPAWN_POS, WORLD_Z};
+pub use input::InputCmd;
+pub use events::{GameEvent, MoveBlockReason};
+pub use instance::{Instance, InstanceId, InstanceManager, InstanceSpawn, InstanceTemplate};
+pub use rng::WorldRng;
+pub use schedule::{Access, Resources, ScheduleError, Scheduler, Stage, System, SystemContext, SystemTiming};
\ No newline at end of file
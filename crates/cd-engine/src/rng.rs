@@ -0,0 +1,92 @@
+use cd_core::{ObjectGuid, Rng};
+use serde::{Deserialize, Serialize};
+
+/// Случайность мира. Все случайное в симуляции должно идти отсюда,
+/// иначе реплеи и тесты перестанут воспроизводиться.
+///
+/// Два вида потоков:
+/// - `main` — общий последовательный поток; его состояние сохраняется вместе с миром.
+/// - `stream`/`entity_stream` — производные потоки, которые зависят только от seed мира
+///   и ключа `(tick, system, guid)`. Результат не зависит от того, кто и в каком порядке
+///   тянул числа до этого, поэтому их можно брать из параллельных систем.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldRng {
+    seed: u64,
+    main: Rng,
+}
+
+impl WorldRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, main: Rng::from_keys(seed, &[system_key("main")]) }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Общий поток. Порядок вызовов важен — только из однопоточного кода.
+    pub fn main(&mut self) -> &mut Rng {
+        &mut self.main
+    }
+
+    /// Поток системы на тик.
+    pub fn stream(&self, tick: u64, system: &str) -> Rng {
+        Rng::from_keys(self.seed, &[system_key(system), tick])
+    }
+
+    /// Поток системы на тик для одной сущности (атака, лут, решение AI).
+    pub fn entity_stream(&self, tick: u64, system: &str, guid: ObjectGuid) -> Rng {
+        Rng::from_keys(self.seed, &[system_key(system), tick, guid.as_u64()])
+    }
+}
+
+/// Стабильный ключ имени системы (FNV-1a).
+/// Не используем std Hasher: его вывод не гарантирован между версиями Rust.
+pub const fn system_key(name: &str) -> u64 {
+    let bytes = name.as_bytes();
+    let mut hash = 0xCBF2_9CE4_8422_2325u64;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+        i += 1;
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roll(mut rng: Rng) -> [u32; 4] {
+        [rng.next_u32(), rng.next_u32(), rng.next_u32(), rng.next_u32()]
+    }
+
+    #[test]
+    fn test_streams_are_keyed_and_order_independent() {
+        let rng = WorldRng::new(42);
+        let guid = ObjectGuid::monster(0, 0, 7);
+
+        let first = roll(rng.entity_stream(10, "combat", guid));
+        // Другие потоки в промежутке ни на что не влияют
+        let _ = roll(rng.stream(10, "combat"));
+        let _ = roll(rng.entity_stream(10, "combat", ObjectGuid::monster(0, 0, 8)));
+        assert_eq!(roll(rng.entity_stream(10, "combat", guid)), first);
+        assert_eq!(roll(WorldRng::new(42).entity_stream(10, "combat", guid)), first);
+
+        assert_ne!(roll(rng.entity_stream(11, "combat", guid)), first);
+        assert_ne!(roll(rng.entity_stream(10, "loot", guid)), first);
+        assert_ne!(roll(WorldRng::new(43).entity_stream(10, "combat", guid)), first);
+    }
+
+    #[test]
+    fn test_state_survives_save() {
+        let mut rng = WorldRng::new(7);
+        rng.main().next_u64();
+
+        let saved = serde_json::to_string(&rng).unwrap();
+        let mut restored: WorldRng = serde_json::from_str(&saved).unwrap();
+        assert_eq!(restored, rng);
+        assert_eq!(restored.main().next_u64(), rng.main().next_u64());
+    }
+}
@@ -0,0 +1,678 @@
+use std::any::TypeId;
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use cd_map::{SpatialGrid, WorldMap, ZoneRegistry};
+use hecs::{CommandBuffer, Component, Entity, Query, World};
+use rayon::prelude::*;
+use crate::events::GameEvent;
+use crate::registry::EntityRegistry;
+use crate::rng::WorldRng;
+
+/// Стадии тика, в порядке выполнения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    /// Разбор намерений, пришедших из ввода
+    Input,
+    Ai,
+    Movement,
+    Combat,
+    /// Смерти, удаление, чистка временных компонентов
+    Cleanup,
+    /// Сбор данных для рассылки клиентам
+    Snapshot,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 6] = [Stage::Input, Stage::Ai, Stage::Movement, Stage::Combat, Stage::Cleanup, Stage::Snapshot];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Input => "input",
+            Stage::Ai => "ai",
+            Stage::Movement => "movement",
+            Stage::Combat => "combat",
+            Stage::Cleanup => "cleanup",
+            Stage::Snapshot => "snapshot",
+        }
+    }
+}
+
+/// Общие ресурсы тика. Системы могут работать параллельно, поэтому все здесь
+/// доступно по разделяемой ссылке; кто что трогает, система объявляет в `access`.
+pub struct Resources<'a> {
+    pub world: &'a World,
+    pub map: &'a WorldMap,
+    pub grid: &'a RwLock<SpatialGrid>,
+    pub zones: &'a ZoneRegistry,
+    pub registry: &'a EntityRegistry,
+    pub rng: &'a WorldRng,
+    pub tick: u64,
+}
+
+/// Доступ к миру для систем тика.
+///
+/// Мир доступен по `&World`: компоненты меняются через `query::<&mut T>()`,
+/// а спавн и удаление идут через `commands` и применяются в конце тика.
+/// Поэтому вставленный через `commands` компонент (например, `MoveIntent` от ИИ)
+/// последующие стадии этого тика не увидят — только следующий тик.
+/// `commands` и `events` у каждой системы свои; планировщик сливает их в порядке систем.
+pub struct SystemContext<'a> {
+    pub world: &'a World,
+    pub map: &'a WorldMap,
+    pub grid: &'a RwLock<SpatialGrid>,
+    pub zones: &'a ZoneRegistry,
+    pub registry: &'a EntityRegistry,
+    pub rng: &'a WorldRng,
+    pub commands: &'a mut CommandBuffer,
+    pub events: &'a mut Vec<GameEvent>,
+    pub tick: u64,
+}
+
+impl SystemContext<'_> {
+    /// Обойти запрос пачками по `batch_size` сущностей на пуле потоков.
+    /// Результаты идут в порядке обхода запроса — от числа потоков ничего не зависит.
+    pub fn par_map<Q, R, F>(&self, batch_size: u32, f: F) -> Vec<R>
+    where
+        Q: Query,
+        for<'q> Q::Item<'q>: Send,
+        F: for<'q> Fn(Entity, Q::Item<'q>) -> R + Sync,
+        R: Send,
+    {
+        let mut query = self.world.query::<Q>();
+        let batches: Vec<_> = query.iter_batched(batch_size.max(1)).collect();
+        let results: Vec<Vec<R>> = batches.into_par_iter().map(|batch| batch.map(|(e, item)| f(e, item)).collect()).collect();
+        results.into_iter().flatten().collect()
+    }
+}
+
+/// Что система читает и пишет. По этому планировщик решает, кого можно запускать одновременно.
+/// Карта и сетка синхронизированы внутри, но без объявления параллельные записи
+/// сделали бы результат зависимым от потоков.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Access {
+    reads: Vec<TypeId>,
+    writes: Vec<TypeId>,
+    map_read: bool,
+    map_write: bool,
+    grid_read: bool,
+    grid_write: bool,
+    exclusive: bool,
+}
+
+impl Access {
+    /// Ничего не трогает.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Конфликтует со всеми: система всегда работает одна.
+    pub fn exclusive() -> Self {
+        Self { exclusive: true, ..Self::default() }
+    }
+
+    pub fn read<T: Component>(mut self) -> Self {
+        self.reads.push(TypeId::of::<T>());
+        self
+    }
+
+    pub fn write<T: Component>(mut self) -> Self {
+        self.writes.push(TypeId::of::<T>());
+        self
+    }
+
+    pub fn read_map(mut self) -> Self {
+        self.map_read = true;
+        self
+    }
+
+    pub fn write_map(mut self) -> Self {
+        self.map_write = true;
+        self
+    }
+
+    pub fn read_grid(mut self) -> Self {
+        self.grid_read = true;
+        self
+    }
+
+    pub fn write_grid(mut self) -> Self {
+        self.grid_write = true;
+        self
+    }
+
+    /// Нельзя запускать одновременно: одна пишет то, что другая читает или пишет.
+    pub fn conflicts(&self, other: &Access) -> bool {
+        let writes_into = |a: &Access, b: &Access| {
+            a.writes.iter().any(|t| b.writes.contains(t) || b.reads.contains(t))
+                || (a.map_write && (b.map_read || b.map_write))
+                || (a.grid_write && (b.grid_read || b.grid_write))
+        };
+        self.exclusive || other.exclusive || writes_into(self, other) || writes_into(other, self)
+    }
+}
+
+/// Система симуляции, запускаемая планировщиком каждый тик.
+pub trait System: Send {
+    /// Уникальное имя: по нему задается порядок и ведется статистика.
+    fn name(&self) -> &'static str;
+
+    fn stage(&self) -> Stage;
+
+    /// Системы, которые должны отработать раньше этой.
+    /// Ссылки на незарегистрированные системы игнорируются.
+    fn after(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Системы, которые должны отработать позже этой.
+    fn before(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Доступ к данным. По умолчанию — эксклюзивный: безопасно, но без параллельности.
+    fn access(&self) -> Access {
+        Access::exclusive()
+    }
+
+    fn run(&mut self, ctx: &mut SystemContext);
+}
+
+/// Время работы системы.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemTiming {
+    pub last: Duration,
+    pub max: Duration,
+    pub total: Duration,
+    pub runs: u64,
+}
+
+impl SystemTiming {
+    pub fn average(&self) -> Duration {
+        if self.runs == 0 {
+            return Duration::ZERO;
+        }
+        self.total / self.runs as u32
+    }
+
+    fn record(&mut self, elapsed: Duration) {
+        self.last = elapsed;
+        self.max = self.max.max(elapsed);
+        self.total += elapsed;
+        self.runs += 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("system '{0}' is already registered")]
+    Duplicate(&'static str),
+    #[error("system '{system}' must run before '{other}', but its stage is later")]
+    StageConflict { system: &'static str, other: &'static str },
+    #[error("ordering cycle through system '{0}'")]
+    Cycle(&'static str),
+}
+
+/// Система вместе с ее буферами вывода.
+struct Slot {
+    system: Box<dyn System>,
+    access: Access,
+    timing: SystemTiming,
+    commands: CommandBuffer,
+    events: Vec<GameEvent>,
+}
+
+/// Планировщик систем: стадии по порядку, внутри стадии — по ограничениям
+/// `after`/`before`, при прочих равных — в порядке регистрации.
+/// Порядок пересчитывается при регистрации, так что тик только идет по готовому списку.
+///
+/// Соседние по порядку системы без конфликтов доступа (`System::access`) и без ограничений
+/// между собой собираются в пачку и идут на пуле потоков. События и команды систем сливаются
+/// в порядке запуска, поэтому результат тот же, что и при последовательном прогоне.
+pub struct Scheduler {
+    systems: Vec<Slot>,
+    // Индексы systems в порядке запуска
+    order: Vec<usize>,
+    // Разбиение order на пачки: [start, end)
+    batches: Vec<(usize, usize)>,
+    /// Запускать пачки параллельно. Выключение полезно для профилирования и отладки.
+    pub parallel: bool,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self { systems: Vec::new(), order: Vec::new(), batches: Vec::new(), parallel: true }
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Зарегистрировать систему. При ошибке порядка система не добавляется.
+    pub fn add(&mut self, system: Box<dyn System>) -> Result<(), ScheduleError> {
+        if self.index_of(system.name()).is_some() {
+            return Err(ScheduleError::Duplicate(system.name()));
+        }
+        self.systems.push(Slot::new(system));
+        match self.sorted() {
+            Ok(order) => {
+                self.order = order;
+                self.batches = self.batched();
+                Ok(())
+            }
+            Err(e) => {
+                self.systems.pop();
+                Err(e)
+            }
+        }
+    }
+
+    /// Зарегистрировать встроенную систему движка. Встроенные идут первыми, и порядок
+    /// между ними задан движком, так что отказ тут — баг движка, а не ошибка вызывающего:
+    /// он ловится в debug-сборке, а в release система встает в порядке регистрации.
+    pub(crate) fn add_builtin(&mut self, system: Box<dyn System>) {
+        let name = system.name();
+        debug_assert!(self.index_of(name).is_none(), "built-in system '{}' registered twice", name);
+        self.systems.push(Slot::new(system));
+        self.order = match self.sorted() {
+            Ok(order) => order,
+            Err(e) => {
+                debug_assert!(false, "built-in system '{}' rejected: {}", name, e);
+                (0..self.systems.len()).collect()
+            }
+        };
+        self.batches = self.batched();
+    }
+
+    /// Прогнать все системы один раз. События дописываются в `events` в порядке систем.
+    /// Команды копятся до `flush_commands`.
+    pub fn run(&mut self, res: &Resources, events: &mut Vec<GameEvent>) {
+        for &(start, end) in &self.batches {
+            let mut batch = Self::batch_slots(&mut self.systems, &self.order[start..end]);
+            if self.parallel && batch.len() > 1 {
+                batch.par_iter_mut().for_each(|slot| slot.run(res));
+            } else {
+                batch.iter_mut().for_each(|slot| slot.run(res));
+            }
+            for slot in batch {
+                events.append(&mut slot.events);
+            }
+        }
+    }
+
+    /// Применить команды систем (спавн, удаление) в порядке запуска.
+    pub fn flush_commands(&mut self, world: &mut World) {
+        for &i in &self.order {
+            self.systems[i].commands.run_on(world);
+        }
+    }
+
+    /// Пачки одновременно запускаемых систем, по порядку.
+    pub fn batches(&self) -> impl Iterator<Item = Vec<&'static str>> + '_ {
+        self.batches.iter().map(|&(start, end)| self.order[start..end].iter().map(|&i| self.systems[i].system.name()).collect())
+    }
+
+    /// Имена систем в порядке запуска.
+    pub fn order(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.order.iter().map(|&i| self.systems[i].system.name())
+    }
+
+    pub fn timing(&self, name: &str) -> Option<SystemTiming> {
+        self.index_of(name).map(|i| self.systems[i].timing)
+    }
+
+    /// Статистика всех систем в порядке запуска.
+    pub fn timings(&self) -> impl Iterator<Item = (&'static str, Stage, SystemTiming)> + '_ {
+        self.order.iter().map(|&i| {
+            let slot = &self.systems[i];
+            (slot.system.name(), slot.system.stage(), slot.timing)
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.systems.iter().position(|s| s.system.name() == name)
+    }
+
+    /// Изменяемые ссылки на слоты пачки в порядке запуска.
+    fn batch_slots<'s>(systems: &'s mut [Slot], batch: &[usize]) -> Vec<&'s mut Slot> {
+        let mut slots: Vec<(usize, &mut Slot)> = systems
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, slot)| batch.iter().position(|&b| b == i).map(|pos| (pos, slot)))
+            .collect();
+        slots.sort_unstable_by_key(|&(pos, _)| pos);
+        slots.into_iter().map(|(_, slot)| slot).collect()
+    }
+
+    /// Жадно режем порядок на пачки: система открывает новую пачку, если конфликтует
+    /// с кем-то из текущей, связана с ним ограничением или стоит в другой стадии.
+    fn batched(&self) -> Vec<(usize, usize)> {
+        let mut batches = Vec::new();
+        let mut start = 0;
+        for end in 0..self.order.len() {
+            let slot = &self.systems[self.order[end]];
+            let split = self.order[start..end].iter().any(|&j| {
+                let other = &self.systems[j];
+                other.system.stage() != slot.system.stage()
+                    || other.access.conflicts(&slot.access)
+                    || Self::ordered(other, slot)
+                    || Self::ordered(slot, other)
+            });
+            if split {
+                batches.push((start, end));
+                start = end;
+            }
+        }
+        if start < self.order.len() {
+            batches.push((start, self.order.len()));
+        }
+        batches
+    }
+
+    /// Есть ли у `a` ограничение порядка относительно `b`.
+    fn ordered(a: &Slot, b: &Slot) -> bool {
+        let name = b.system.name();
+        a.system.after().contains(&name) || a.system.before().contains(&name)
+    }
+
+    /// Топологическая сортировка (Kahn). Готовые системы берутся по (стадия, индекс) —
+    /// так порядок стабилен и не зависит от обхода хэш-таблиц.
+    fn sorted(&self) -> Result<Vec<usize>, ScheduleError> {
+        let n = self.systems.len();
+        let mut edges: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut incoming = vec![0usize; n];
+
+        for (i, slot) in self.systems.iter().enumerate() {
+            let system = &slot.system;
+            let after = system.after().iter().filter_map(|name| self.index_of(name)).map(|j| (j, i));
+            let before = system.before().iter().filter_map(|name| self.index_of(name)).map(|j| (i, j));
+            for (first, then) in after.chain(before) {
+                let (a, b) = (&self.systems[first].system, &self.systems[then].system);
+                if a.stage() > b.stage() {
+                    return Err(ScheduleError::StageConflict { system: a.name(), other: b.name() });
+                }
+                // Между стадиями порядок и так задан
+                if a.stage() == b.stage() {
+                    edges[first].push(then);
+                    incoming[then] += 1;
+                }
+            }
+        }
+
+        let mut ready: BTreeSet<(Stage, usize)> =
+            (0..n).filter(|&i| incoming[i] == 0).map(|i| (self.systems[i].system.stage(), i)).collect();
+        let mut order = Vec::with_capacity(n);
+        while let Some((_, i)) = ready.pop_first() {
+            order.push(i);
+            for &j in &edges[i] {
+                incoming[j] -= 1;
+                if incoming[j] == 0 {
+                    ready.insert((self.systems[j].system.stage(), j));
+                }
+            }
+        }
+
+        match (0..n).find(|&i| incoming[i] > 0) {
+            Some(i) => Err(ScheduleError::Cycle(self.systems[i].system.name())),
+            None => Ok(order),
+        }
+    }
+}
+
+impl Slot {
+    fn new(system: Box<dyn System>) -> Self {
+        let access = system.access();
+        Self { system, access, timing: SystemTiming::default(), commands: CommandBuffer::new(), events: Vec::new() }
+    }
+
+    fn run(&mut self, res: &Resources) {
+        let mut ctx = SystemContext {
+            world: res.world,
+            map: res.map,
+            grid: res.grid,
+            zones: res.zones,
+            registry: res.registry,
+            rng: res.rng,
+            commands: &mut self.commands,
+            events: &mut self.events,
+            tick: res.tick,
+        };
+        let start = Instant::now();
+        self.system.run(&mut ctx);
+        self.timing.record(start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cd_core::{ObjectGuid, WorldPos};
+    use cd_ecs::components::{Position, Stats};
+    use std::sync::{Arc, Mutex};
+
+    struct Probe {
+        name: &'static str,
+        stage: Stage,
+        after: Vec<&'static str>,
+        before: Vec<&'static str>,
+        log: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl System for Probe {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn stage(&self) -> Stage {
+            self.stage
+        }
+
+        fn after(&self) -> &[&'static str] {
+            &self.after
+        }
+
+        fn before(&self) -> &[&'static str] {
+            &self.before
+        }
+
+        fn run(&mut self, _ctx: &mut SystemContext) {
+            self.log.lock().unwrap().push(self.name);
+        }
+    }
+
+    fn probe(log: &Arc<Mutex<Vec<&'static str>>>, name: &'static str, stage: Stage) -> Probe {
+        Probe { name, stage, after: Vec::new(), before: Vec::new(), log: log.clone() }
+    }
+
+    fn run_once(scheduler: &mut Scheduler) {
+        run_tick(scheduler, &mut World::new(), 1);
+    }
+
+    fn run_tick(scheduler: &mut Scheduler, world: &mut World, tick: u64) -> Vec<GameEvent> {
+        let map = WorldMap::new();
+        let grid = RwLock::new(SpatialGrid::new());
+        let zones = ZoneRegistry::new();
+        let registry = EntityRegistry::new();
+        let rng = WorldRng::new(9);
+        let mut events = Vec::new();
+        let res = Resources { world, map: &map, grid: &grid, zones: &zones, registry: &registry, rng: &rng, tick };
+        scheduler.run(&res, &mut events);
+        scheduler.flush_commands(world);
+        events
+    }
+
+    #[test]
+    fn test_stages_then_constraints_then_registration_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut scheduler = Scheduler::new();
+
+        scheduler.add(Box::new(probe(&log, "snapshot", Stage::Snapshot))).unwrap();
+        scheduler.add(Box::new(Probe { after: vec!["physics", "missing"], ..probe(&log, "triggers", Stage::Movement) })).unwrap();
+        scheduler.add(Box::new(probe(&log, "physics", Stage::Movement))).unwrap();
+        scheduler.add(Box::new(probe(&log, "brain", Stage::Ai))).unwrap();
+        scheduler.add(Box::new(Probe { before: vec!["brain"], ..probe(&log, "perception", Stage::Ai) })).unwrap();
+
+        let expected = ["perception", "brain", "physics", "triggers", "snapshot"];
+        assert_eq!(scheduler.order().collect::<Vec<_>>(), expected);
+        assert_eq!(scheduler.batches().count(), 5); // Доступ по умолчанию эксклюзивный
+
+        run_once(&mut scheduler);
+        run_once(&mut scheduler);
+        assert_eq!(&log.lock().unwrap()[..5], expected);
+
+        let timing = scheduler.timing("physics").unwrap();
+        assert_eq!(timing.runs, 2);
+        assert!(timing.max >= timing.last && timing.total >= timing.max);
+        assert_eq!(scheduler.timings().count(), 5);
+    }
+
+    #[test]
+    fn test_invalid_orderings_are_rejected() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut scheduler = Scheduler::new();
+
+        scheduler.add(Box::new(Probe { after: vec!["b"], ..probe(&log, "a", Stage::Combat) })).unwrap();
+        assert_eq!(scheduler.add(Box::new(probe(&log, "a", Stage::Combat))), Err(ScheduleError::Duplicate("a")));
+        assert_eq!(
+            scheduler.add(Box::new(Probe { after: vec!["a"], ..probe(&log, "b", Stage::Combat) })),
+            Err(ScheduleError::Cycle("a"))
+        );
+        assert_eq!(
+            scheduler.add(Box::new(Probe { before: vec!["a"], ..probe(&log, "late", Stage::Cleanup) })),
+            Err(ScheduleError::StageConflict { system: "late", other: "a" })
+        );
+        // Отклоненные системы не остались в планировщике
+        assert_eq!(scheduler.order().collect::<Vec<_>>(), ["a"]);
+    }
+
+    // Яд: случайный урон по сущностям, пачками на пуле потоков
+    struct Poison;
+
+    impl System for Poison {
+        fn name(&self) -> &'static str {
+            "poison"
+        }
+
+        fn stage(&self) -> Stage {
+            Stage::Combat
+        }
+
+        fn access(&self) -> Access {
+            Access::new().write::<Stats>().read::<Position>()
+        }
+
+        fn run(&mut self, ctx: &mut SystemContext) {
+            let (rng, tick) = (ctx.rng, ctx.tick);
+            let hits = ctx.par_map::<(&mut Stats, &Position), _, _>(64, |entity, (stats, _)| {
+                let guid = ObjectGuid::monster(0, 0, entity.id());
+                let amount = rng.entity_stream(tick, "poison", guid).below(4) as i32;
+                stats.hp -= amount;
+                GameEvent::EntityDamaged { entity_guid: guid, amount }
+            });
+            ctx.events.extend(hits);
+        }
+    }
+
+    // Читает те же позиции — может идти рядом с ядом
+    struct Scan;
+
+    impl System for Scan {
+        fn name(&self) -> &'static str {
+            "scan"
+        }
+
+        fn stage(&self) -> Stage {
+            Stage::Combat
+        }
+
+        fn access(&self) -> Access {
+            Access::new().read::<Position>().read_map()
+        }
+
+        fn run(&mut self, ctx: &mut SystemContext) {
+            for (_, pos) in ctx.world.query::<&Position>().iter() {
+                if pos.0.x() as u64 % 50 == ctx.tick {
+                    ctx.events.push(GameEvent::FireStarted { pos: pos.0 });
+                }
+            }
+        }
+    }
+
+    struct Reaper;
+
+    impl System for Reaper {
+        fn name(&self) -> &'static str {
+            "reaper"
+        }
+
+        fn stage(&self) -> Stage {
+            Stage::Cleanup
+        }
+
+        fn access(&self) -> Access {
+            Access::new().read::<Stats>()
+        }
+
+        fn run(&mut self, ctx: &mut SystemContext) {
+            for (entity, stats) in ctx.world.query::<&Stats>().iter() {
+                if stats.hp <= 0 {
+                    ctx.commands.despawn(entity);
+                }
+            }
+        }
+    }
+
+    fn simulate(parallel: bool) -> (Vec<GameEvent>, Vec<i32>) {
+        let mut scheduler = Scheduler::new();
+        scheduler.parallel = parallel;
+        scheduler.add(Box::new(Reaper)).unwrap();
+        scheduler.add(Box::new(Scan)).unwrap();
+        scheduler.add(Box::new(Poison)).unwrap();
+        assert_eq!(scheduler.batches().collect::<Vec<_>>(), vec![vec!["scan", "poison"], vec!["reaper"]]);
+
+        let mut world = World::new();
+        for i in 0..2000 {
+            let hp = 3 + i % 7;
+            world.spawn((Position(WorldPos::new(i, 0, 0)), Stats { hp, max_hp: hp, mana: 0, max_mana: 0 }));
+        }
+
+        let mut events = Vec::new();
+        for tick in 1..=5 {
+            events.extend(run_tick(&mut scheduler, &mut world, tick));
+        }
+        let mut hp: Vec<i32> = world.query::<&Stats>().iter().map(|(_, s)| s.hp).collect();
+        hp.sort_unstable();
+        (events, hp)
+    }
+
+    #[test]
+    fn test_parallel_run_matches_sequential() {
+        let sequential = simulate(false);
+        assert!(sequential.1.len() < 2000); // Кто-то умер и был удален командами
+        for _ in 0..3 {
+            assert_eq!(simulate(true), sequential);
+        }
+    }
+
+    #[test]
+    fn test_access_conflicts() {
+        let reader = Access::new().read::<Position>();
+        let writer = Access::new().write::<Position>();
+        assert!(!reader.conflicts(&reader));
+        assert!(reader.conflicts(&writer) && writer.conflicts(&reader));
+        assert!(!writer.conflicts(&Access::new().write::<Stats>()));
+        assert!(Access::new().write_grid().conflicts(&Access::new().read_grid()));
+        assert!(!Access::new().read_map().conflicts(&Access::new().read_map()));
+        assert!(Access::exclusive().conflicts(&Access::new()));
+    }
+}
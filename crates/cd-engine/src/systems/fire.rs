@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use ahash::{HashSet, HashSetExt};
+use cd_core::{Direction, GridLogic, Rng, WorldPos};
+use cd_ecs::components::Stats;
+use cd_map::{MaterialRegistry, SpatialGrid, WorldMap};
+use hecs::World;
+use crate::events::GameEvent;
+use crate::registry::EntityRegistry;
+
+/// Урон сущности, стоящей в огне, за тик.
+pub const FIRE_DAMAGE: i32 = 5;
+/// Радиус света от горящего тайла.
+pub const FIRE_LIGHT_RADIUS: i32 = 4;
+
+/// Система огня.
+/// Горение хранится оверлеем поверх карты, сгруппированным по чанкам:
+/// тик обходит только чанки, где что-то горит, а остальной мир не трогает.
+///
+/// Своего `Rng` не держит: поток на тик дает `WorldRng::stream`,
+/// поэтому после загрузки мира огонь ведет себя так же, как без перезапуска.
+#[derive(Debug, Default)]
+pub struct FireSystem {
+    // chunk_key -> (позиция -> оставшееся топливо в тиках)
+    burning: HashMap<WorldPos, HashMap<WorldPos, u8>>,
+}
+
+impl FireSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Поджечь тайл. Вернет false, если материал не горит или тайл уже горит.
+    pub fn ignite(&mut self, map: &WorldMap, materials: &MaterialRegistry, pos: WorldPos) -> bool {
+        if self.is_burning(pos) {
+            return false;
+        }
+        let tile = map.get_tile(pos);
+        match materials.get(tile.material).filter(|def| def.is_flammable()) {
+            Some(def) => {
+                self.burning.entry(pos.chunk_key()).or_default().insert(pos, def.fuel);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_burning(&self, pos: WorldPos) -> bool {
+        self.burning.get(&pos.chunk_key()).is_some_and(|c| c.contains_key(&pos))
+    }
+
+    /// Чанки, в которых сейчас есть огонь.
+    pub fn active_chunks(&self) -> impl Iterator<Item = WorldPos> + '_ {
+        self.burning.keys().copied()
+    }
+
+    /// Источники света от огня: (позиция, радиус).
+    pub fn light_sources(&self) -> impl Iterator<Item = (WorldPos, i32)> + '_ {
+        self.burning.values().flat_map(|c| c.keys()).map(|&pos| (pos, FIRE_LIGHT_RADIUS))
+    }
+
+    /// Один тик огня: распространение на соседей и выгорание.
+    pub fn step(&mut self, map: &WorldMap, materials: &MaterialRegistry, rng: &mut Rng, events: &mut Vec<GameEvent>) {
+        // Порядок обхода HashMap не детерминирован — сортируем, чтобы RNG
+        // тратился в одном и том же порядке при одном и том же seed.
+        let tiles = self.sorted_tiles();
+
+        // 1. Распространение. Vec держит порядок поджога, set — быструю проверку повторов
+        let mut ignited: Vec<(WorldPos, u8)> = Vec::new();
+        let mut ignited_set: HashSet<WorldPos> = HashSet::new();
+        for &(pos, _) in &tiles {
+            for dir in Direction::ORTHOGONAL {
+                let next = pos.shift(dir);
+                if self.is_burning(next) || ignited_set.contains(&next) {
+                    continue;
+                }
+                let tile = map.get_tile(next);
+                if let Some(def) = materials.get(tile.material).filter(|def| def.is_flammable())
+                    && rng.chance(def.flammability)
+                {
+                    ignited.push((next, def.fuel));
+                    ignited_set.insert(next);
+                }
+            }
+        }
+
+        // 2. Выгорание
+        for (pos, fuel) in tiles {
+            let chunk_key = pos.chunk_key();
+            if fuel > 1 {
+                if let Some(left) = self.burning.get_mut(&chunk_key).and_then(|c| c.get_mut(&pos)) {
+                    *left = fuel - 1;
+                }
+                continue;
+            }
+
+            if let Some(chunk) = self.burning.get_mut(&chunk_key) {
+                chunk.remove(&pos);
+                if chunk.is_empty() {
+                    self.burning.remove(&chunk_key);
+                }
+            }
+
+            let tile = map.get_tile(pos);
+            if let Some(ash) = materials.get(tile.material).and_then(|def| def.burns_to) {
+                map.set_tile(pos, ash);
+                events.push(GameEvent::TileChanged { pos, tile: ash });
+            }
+            events.push(GameEvent::FireEnded { pos });
+        }
+
+        // 3. Новые очаги (начнут распространяться со следующего тика)
+        for (pos, fuel) in ignited {
+            self.burning.entry(pos.chunk_key()).or_default().insert(pos, fuel);
+            events.push(GameEvent::FireStarted { pos });
+        }
+    }
+
+    /// Урон всем, кто стоит в огне.
+    pub(crate) fn damage_entities(
+        &self,
+        world: &mut World,
+        grid: &SpatialGrid,
+        registry: &EntityRegistry,
+        events: &mut Vec<GameEvent>,
+    ) {
+        for (pos, _) in self.sorted_tiles() {
+            for guid in grid.entities_at(pos) {
+                let Some(entity) = registry.get_entity(guid) else { continue };
+                let Ok(stats) = world.query_one_mut::<&mut Stats>(entity) else { continue };
+
+                stats.hp -= FIRE_DAMAGE;
+                events.push(GameEvent::EntityDamaged { entity_guid: guid, amount: FIRE_DAMAGE });
+            }
+        }
+    }
+
+    // --- Private Helpers ---
+
+    fn sorted_tiles(&self) -> Vec<(WorldPos, u8)> {
+        let mut tiles: Vec<(WorldPos, u8)> = self
+            .burning
+            .values()
+            .flat_map(|c| c.iter().map(|(&pos, &fuel)| (pos, fuel)))
+            .collect();
+        tiles.sort_unstable_by_key(|(pos, _)| (pos.z(), pos.y(), pos.x()));
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::WorldRng;
+    use cd_map::{MaterialDef, Tile, TileFlags};
+
+    const GRASS: Tile = Tile { material: 1, flags: TileFlags::WALKABLE, variant: 0 };
+    const ASH: Tile = Tile { material: 2, flags: TileFlags::WALKABLE, variant: 0 };
+
+    fn setup() -> (WorldMap, MaterialRegistry) {
+        let mut materials = MaterialRegistry::new();
+        materials.register(MaterialDef::simple(1, "Grass", TileFlags::WALKABLE).flammable(50, 3, ASH));
+        materials.register(MaterialDef::simple(2, "Ash", TileFlags::WALKABLE));
+
+        let map = WorldMap::new();
+        for y in 0..8 {
+            for x in 0..8 {
+                map.set_tile(WorldPos::new(x, y, 0), GRASS);
+            }
+        }
+        (map, materials)
+    }
+
+    fn simulate(seed: u64, ticks: usize) -> Vec<GameEvent> {
+        let (map, materials) = setup();
+        let rng = WorldRng::new(seed);
+        let mut fire = FireSystem::new();
+        let mut events = Vec::new();
+
+        assert!(fire.ignite(&map, &materials, WorldPos::new(4, 4, 0)));
+        for tick in 0..ticks as u64 {
+            fire.step(&map, &materials, &mut rng.stream(tick, "fire"), &mut events);
+        }
+        events
+    }
+
+    #[test]
+    fn test_fire_is_deterministic() {
+        assert_eq!(simulate(1234, 20), simulate(1234, 20));
+    }
+
+    #[test]
+    fn test_fire_burns_out_to_ash() {
+        let (map, materials) = setup();
+        let mut fire = FireSystem::new();
+        let mut rng = Rng::new(0);
+        let mut events = Vec::new();
+        let pos = WorldPos::new(0, 0, 0);
+
+        fire.ignite(&map, &materials, pos);
+        assert!(!fire.ignite(&map, &materials, pos)); // Уже горит
+
+        for _ in 0..3 {
+            fire.step(&map, &materials, &mut rng, &mut events);
+        }
+        assert!(!fire.is_burning(pos));
+        assert_eq!(map.get_tile(pos), ASH);
+        assert!(events.contains(&GameEvent::FireEnded { pos }));
+        assert!(!fire.ignite(&map, &materials, pos)); // Пепел не горит
+    }
+}
@@ -1 +1,2 @@
-pub mod movement;
\ No newline at end of file
+pub mod movement;
+pub mod fire;
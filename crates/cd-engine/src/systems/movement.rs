@@ -1,19 +1,177 @@
-use cd_core::ObjectGuid;
-use cd_ecs::components::{Position, Name};
-use cd_map::{WorldMap, SpatialGrid};
-use hecs::{World, Entity};
-use std::collections::HashMap;
-
-/// Пример системы. В будущем она будет двигать Velocity -> Position.
-pub fn run_movement(
-    world: &mut World,
-    _map: &WorldMap,
-    _grid: &mut SpatialGrid,
-    _index: &HashMap<ObjectGuid, Entity>
-) {
-    // Пример итерации: Найти всех, у кого есть Имя и Позиция
-    for (_id, (name, pos)) in world.query::<(&Name, &Position)>().iter() {
-        // Тут могла быть логика интерполяции или проверки физики
-        // tracing::trace!("Processing movement for {}: {:?}", name.0, pos.0);
-    }
-}
\ No newline at end of file
+use cd_core::{Direction, GridLogic, ObjectGuid, WorldPos};
+use cd_ecs::components::{MoveIntent, Position};
+use cd_map::{CornerCutting, SpatialGrid, WorldMap};
+use std::sync::PoisonError;
+use crate::events::{GameEvent, MoveBlockReason};
+use crate::schedule::{Access, Stage, System, SystemContext};
+
+/// Исполняет `MoveIntent`: один шаг за тик в соседнюю клетку или по лестнице.
+/// Шаг отклоняется, если цель дальше соседней, в стене, за стыком стен или занята.
+/// Порталы в цели переносят дальше.
+///
+/// Видит только намерения, которые уже лежат в мире к началу тика (их вставляет ввод).
+/// Намерения от систем стадии `Ai` идут через `commands` и исполнятся на следующем тике.
+pub struct MovementSystem;
+
+impl System for MovementSystem {
+    fn name(&self) -> &'static str {
+        "movement"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Movement
+    }
+
+    fn access(&self) -> Access {
+        Access::new().write::<Position>().read::<MoveIntent>().read_map().write_grid()
+    }
+
+    fn run(&mut self, ctx: &mut SystemContext) {
+        let world = ctx.world;
+        let mut grid = ctx.grid.write().unwrap_or_else(PoisonError::into_inner);
+
+        for (entity, (pos, intent)) in world.query::<(&mut Position, &MoveIntent)>().iter() {
+            ctx.commands.remove_one::<MoveIntent>(entity);
+            let Some(entity_guid) = ctx.registry.get_guid(entity) else { continue };
+            let from = pos.0;
+            let target = intent.target;
+
+            let dest = match check_step(ctx.map, &grid, entity_guid, from, target) {
+                Ok(dest) => dest,
+                Err(reason) => {
+                    ctx.events.push(GameEvent::MoveBlocked { entity_guid, target, reason });
+                    continue;
+                }
+            };
+
+            pos.0 = dest;
+            grid.move_entity(entity_guid, from, dest);
+            ctx.events.push(GameEvent::EntityMoved { entity_guid, from, to: dest });
+            if dest != target {
+                ctx.events.push(GameEvent::Teleported { entity_guid, from: target, to: dest });
+            }
+
+            // Триггеры зон
+            let transition = ctx.zones.transition(from, dest);
+            for zone in transition.left {
+                ctx.events.push(GameEvent::ZoneLeft { entity_guid, zone });
+            }
+            for zone in transition.entered {
+                ctx.events.push(GameEvent::ZoneEntered { entity_guid, zone });
+            }
+        }
+    }
+}
+
+/// Проверка шага. Ok — куда сущность попадет в итоге (с учетом портала).
+fn check_step(
+    map: &WorldMap,
+    grid: &SpatialGrid,
+    guid: ObjectGuid,
+    from: WorldPos,
+    target: WorldPos,
+) -> Result<WorldPos, MoveBlockReason> {
+    if target.z() != from.z() {
+        // Смена этажа — только по лестнице под ногами
+        let dir = if target.z() > from.z() { Direction::Up } else { Direction::Down };
+        if map.stair_destination(from, dir) != Some(target) {
+            return Err(MoveBlockReason::NoStairs);
+        }
+    } else if from.chebyshev_distance(target) > 1 {
+        return Err(MoveBlockReason::TooFar);
+    }
+
+    if map.is_solid_fast(target) {
+        return Err(MoveBlockReason::Solid);
+    }
+
+    // Диагональ сквозь стык двух стен запрещена
+    let dir = Direction::from_positions(from, target);
+    if target.z() == from.z() && dir.is_diagonal() && !map.can_step(from, dir, CornerCutting::Never) {
+        return Err(MoveBlockReason::CornerCut);
+    }
+
+    // Портал переносит дальше, в том числе в другое измерение
+    let dest = map.portal_destination(target).filter(|&d| !map.is_solid_fast(d)).unwrap_or(target);
+
+    if grid.entities_at(dest).any(|other| other != guid) {
+        return Err(MoveBlockReason::Occupied);
+    }
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Engine, InputCmd};
+    use crate::events::{GameEvent, MoveBlockReason};
+    use cd_core::{ObjectGuid, WorldPos};
+    use cd_map::{Tile, TileFlags, ZoneKind, ZoneShape};
+
+    const WALL: Tile = Tile { material: 1, flags: TileFlags::SOLID, variant: 0 };
+
+    fn step(engine: &mut Engine, guid: ObjectGuid, target: WorldPos) -> Vec<GameEvent> {
+        engine.tick(vec![InputCmd::Move { entity_guid: guid, target }]);
+        engine.drain_events().collect()
+    }
+
+    #[test]
+    fn test_step_and_rejections() {
+        let mut engine = Engine::new();
+        let (a, b) = (ObjectGuid::player(0, 0, 1), ObjectGuid::player(0, 0, 2));
+        let start = WorldPos::new(0, 0, 0);
+        engine.spawn_player(a, "A".into(), start);
+        engine.spawn_player(b, "B".into(), WorldPos::new(2, 0, 0));
+        engine.map.set_tile(WorldPos::new(0, 1, 0), WALL);
+
+        let moved = WorldPos::new(1, 0, 0);
+        assert_eq!(step(&mut engine, a, moved), vec![GameEvent::EntityMoved { entity_guid: a, from: start, to: moved }]);
+        assert_eq!(engine.grid.position_of(a), Some(moved));
+
+        let blocked = |target, reason| vec![GameEvent::MoveBlocked { entity_guid: a, target, reason }];
+        let cases = [
+            (WorldPos::new(3, 0, 0), MoveBlockReason::TooFar),
+            (WorldPos::new(2, 0, 0), MoveBlockReason::Occupied),
+            (WorldPos::new(1, 0, 1), MoveBlockReason::NoStairs),
+        ];
+        for (target, reason) in cases {
+            assert_eq!(step(&mut engine, a, target), blocked(target, reason));
+        }
+
+        // Назад к стене: прямо нельзя, по диагонали мимо стыка — тоже
+        assert_eq!(step(&mut engine, a, start), vec![GameEvent::EntityMoved { entity_guid: a, from: moved, to: start }]);
+        assert_eq!(step(&mut engine, a, WorldPos::new(0, 1, 0)), blocked(WorldPos::new(0, 1, 0), MoveBlockReason::Solid));
+        engine.map.set_tile(WorldPos::new(1, 0, 0), WALL);
+        assert_eq!(step(&mut engine, a, WorldPos::new(1, 1, 0)), blocked(WorldPos::new(1, 1, 0), MoveBlockReason::CornerCut));
+
+        // Намерение снимается: без нового ввода ничего не происходит
+        engine.tick(vec![]);
+        assert_eq!(engine.drain_events().count(), 0);
+        assert_eq!(engine.grid.position_of(a), Some(start));
+        // Зоны: вход и выход
+        let arena = engine.zones.add(
+            "Arena",
+            ZoneKind::BossArena,
+            ZoneShape::Rect { min: WorldPos::new(-3, -3, 0), max: WorldPos::new(-1, 3, 0) },
+        );
+        let inside = WorldPos::new(-1, 0, 0);
+        assert_eq!(step(&mut engine, a, inside), vec![
+            GameEvent::EntityMoved { entity_guid: a, from: start, to: inside },
+            GameEvent::ZoneEntered { entity_guid: a, zone: arena },
+        ]);
+        assert_eq!(step(&mut engine, a, start), vec![
+            GameEvent::EntityMoved { entity_guid: a, from: inside, to: start },
+            GameEvent::ZoneLeft { entity_guid: a, zone: arena },
+        ]);
+
+        // Портал переносит дальше — и сразу в зону
+        let gate = WorldPos::new(0, -1, 0);
+        let dest = WorldPos::new(-2, -2, 0);
+        engine.map.place_portal(gate, Tile::default(), dest);
+        assert_eq!(step(&mut engine, a, gate), vec![
+            GameEvent::EntityMoved { entity_guid: a, from: start, to: dest },
+            GameEvent::Teleported { entity_guid: a, from: gate, to: dest },
+            GameEvent::ZoneEntered { entity_guid: a, zone: arena },
+        ]);
+        assert_eq!(engine.grid.position_of(a), Some(dest));
+    }
+}
@@ -1,6 +1,8 @@
 // Выравниваем маску по 32 байта для AVX инструкций (хотя используем u64)
 #[repr(C, align(32))]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
 pub struct BitMask256 {
     pub data: [u64; 4],
 }
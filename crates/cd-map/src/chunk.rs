@@ -76,6 +76,8 @@ impl ChunkBuilder {
 /// Хранится как плоский массив для максимальной скорости доступа (L1 cache friendly).
 #[repr(C, align(64))]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
 pub struct Chunk {
     // Indices теперь просто массив байт внутри структуры
     pub indices: [u8; CHUNK_AREA],
@@ -226,6 +228,24 @@ impl Chunk {
     }
 }
 
+#[cfg(feature = "rkyv")]
+impl ArchivedChunk {
+    /// Чтение тайла прямо из архива, без десериализации чанка.
+    pub fn get_tile(&self, lx: usize, ly: usize) -> Tile {
+        if lx >= CHUNK_SIZE as usize || ly >= CHUNK_SIZE as usize {
+            return Tile::default();
+        }
+        let pal_idx = self.indices[(ly << CHUNK_SHIFT) | lx];
+        Tile::unpack(self.palette[pal_idx as usize])
+    }
+
+    pub fn is_solid_local(&self, lx: usize, ly: usize) -> bool {
+        if lx >= CHUNK_SIZE as usize || ly >= CHUNK_SIZE as usize { return false; }
+        let idx = (ly << CHUNK_SHIFT) | lx;
+        (self.solid_mask.data[idx >> 6] >> (idx & 63)) & 1 != 0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -285,4 +305,36 @@ mod tests {
         // Маска должна обновиться мгновенно
         assert!(chunk.is_solid_local(5, 5));
     }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_chunk_archive_roundtrip() {
+        let wall = Tile { material: 3, flags: TileFlags::SOLID | TileFlags::OPAQUE, variant: 1 };
+        let floor = Tile { material: 4, flags: TileFlags::WALKABLE, variant: 0 };
+        let mut chunk = Chunk::new();
+        chunk.set_tile(0, 0, wall);
+        chunk.set_tile(15, 15, wall);
+        chunk.set_tile(7, 8, floor);
+
+        let bytes = rkyv::to_bytes::<_, 4096>(&chunk).unwrap();
+        let archived = rkyv::check_archived_root::<Chunk>(&bytes).unwrap();
+
+        // Чтение на месте совпадает с исходным чанком
+        for ly in 0..CHUNK_SIZE as usize {
+            for lx in 0..CHUNK_SIZE as usize {
+                assert_eq!(archived.get_tile(lx, ly), chunk.get_tile(lx, ly));
+                assert_eq!(archived.is_solid_local(lx, ly), chunk.is_solid_local(lx, ly));
+            }
+        }
+        assert!(archived.is_solid_local(15, 15));
+        assert!(!archived.is_solid_local(7, 8));
+        // Вне чанка — пустота, без паники
+        assert_eq!(archived.get_tile(16, 0), Tile::default());
+        assert!(!archived.is_solid_local(0, 16));
+
+        let restored: Chunk = rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible).unwrap();
+        assert_eq!(restored.get_tile(7, 8), floor);
+        assert!(restored.is_solid_local(0, 0));
+        assert_eq!(restored.palette_len, chunk.palette_len);
+    }
 }
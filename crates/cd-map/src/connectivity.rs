@@ -0,0 +1,221 @@
+use std::collections::VecDeque;
+use cd_core::{Direction, WorldPos};
+use crate::WorldMap;
+
+/// Идентификатор связной области (компоненты) внутри `Connectivity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ComponentId(pub u32);
+
+/// Разметка связных областей проходимых тайлов в ограниченном прямоугольнике одного Z-уровня.
+///
+/// Считается один раз (flood fill), после чего все запросы O(1).
+/// Генераторы используют её для поиска замурованных комнат,
+/// AI — чтобы сразу отказаться от недостижимой цели.
+#[derive(Debug, Clone)]
+pub struct Connectivity {
+    min_x: i32,
+    min_y: i32,
+    z: i32,
+    width: usize,
+    height: usize,
+
+    // 0 - непроходимый тайл, иначе ComponentId + 1
+    labels: Vec<u32>,
+    // Размер каждой компоненты в тайлах (индекс = ComponentId)
+    sizes: Vec<usize>,
+}
+
+impl Connectivity {
+    /// Размечает область `[min, max]` (включительно, по X/Y) на уровне `min.z()`.
+    /// `neighbours` задает связность: `&Direction::ORTHOGONAL` (4-way) или `&Direction::ALL_2D` (8-way).
+    pub fn compute(map: &WorldMap, min: WorldPos, max: WorldPos, neighbours: &[Direction]) -> Self {
+        let (min_x, max_x) = (min.x().min(max.x()), min.x().max(max.x()));
+        let (min_y, max_y) = (min.y().min(max.y()), min.y().max(max.y()));
+        let z = min.z();
+
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+
+        // 1. Снимаем маску проходимости один раз, чтобы не дергать локи карты в BFS
+        let mut walkable = vec![false; width * height];
+        for ly in 0..height {
+            for lx in 0..width {
+                let pos = WorldPos::new(min_x + lx as i32, min_y + ly as i32, z);
+                walkable[ly * width + lx] = map.get_tile(pos).is_walkable();
+            }
+        }
+
+        let mut labels = vec![0u32; width * height];
+        let mut sizes = Vec::new();
+        let mut queue = VecDeque::new();
+
+        // 2. Flood fill по каждому еще не размеченному проходимому тайлу
+        for start in 0..labels.len() {
+            if !walkable[start] || labels[start] != 0 {
+                continue;
+            }
+
+            let label = sizes.len() as u32 + 1;
+            let mut size = 0;
+            labels[start] = label;
+            queue.push_back(start);
+
+            while let Some(idx) = queue.pop_front() {
+                size += 1;
+                let (x, y) = ((idx % width) as i32, (idx / width) as i32);
+
+                for dir in neighbours {
+                    let (dx, dy, _) = dir.offset();
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32 {
+                        continue;
+                    }
+
+                    let n_idx = ny as usize * width + nx as usize;
+                    if walkable[n_idx] && labels[n_idx] == 0 {
+                        labels[n_idx] = label;
+                        queue.push_back(n_idx);
+                    }
+                }
+            }
+
+            sizes.push(size);
+        }
+
+        Self { min_x, min_y, z, width, height, labels, sizes }
+    }
+
+    /// Компонента, которой принадлежит тайл.
+    /// `None`, если тайл непроходим или лежит вне размеченной области.
+    pub fn component_at(&self, pos: WorldPos) -> Option<ComponentId> {
+        let idx = self.index_of(pos)?;
+        match self.labels[idx] {
+            0 => None,
+            label => Some(ComponentId(label - 1)),
+        }
+    }
+
+    /// Можно ли дойти из `a` в `b`, не выходя за пределы области.
+    pub fn is_connected(&self, a: WorldPos, b: WorldPos) -> bool {
+        match (self.component_at(a), self.component_at(b)) {
+            (Some(ca), Some(cb)) => ca == cb,
+            _ => false,
+        }
+    }
+
+    /// Количество найденных компонент.
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Размер компоненты в тайлах.
+    pub fn size(&self, id: ComponentId) -> usize {
+        self.sizes.get(id.0 as usize).copied().unwrap_or(0)
+    }
+
+    /// Самая большая компонента (при равенстве — с меньшим id).
+    pub fn largest(&self) -> Option<ComponentId> {
+        self.components().max_by(|a, b| self.size(*a).cmp(&self.size(*b)).then(b.cmp(a)))
+    }
+
+    /// Все компоненты по порядку.
+    pub fn components(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        (0..self.sizes.len() as u32).map(ComponentId)
+    }
+
+    /// Все компоненты, кроме самой большой — кандидаты на "замурованные" комнаты.
+    pub fn disconnected(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        let largest = self.largest();
+        self.components().filter(move |&id| Some(id) != largest)
+    }
+
+    /// Тайлы, принадлежащие компоненте.
+    pub fn tiles(&self, id: ComponentId) -> impl Iterator<Item = WorldPos> + '_ {
+        let label = id.0 + 1;
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(move |&(_, &l)| l == label)
+            .map(move |(idx, _)| self.pos_of(idx))
+    }
+
+    // --- Private Helpers ---
+
+    fn index_of(&self, pos: WorldPos) -> Option<usize> {
+        if pos.z() != self.z {
+            return None;
+        }
+        let lx = pos.x() - self.min_x;
+        let ly = pos.y() - self.min_y;
+        if lx < 0 || ly < 0 || lx >= self.width as i32 || ly >= self.height as i32 {
+            return None;
+        }
+        Some(ly as usize * self.width + lx as usize)
+    }
+
+    fn pos_of(&self, idx: usize) -> WorldPos {
+        let lx = (idx % self.width) as i32;
+        let ly = (idx / self.width) as i32;
+        WorldPos::new(self.min_x + lx, self.min_y + ly, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tile, TileFlags};
+
+    const FLOOR: Tile = Tile { material: 1, flags: TileFlags::WALKABLE, variant: 0 };
+    const WALL: Tile = Tile { material: 2, flags: TileFlags::SOLID, variant: 0 };
+
+    /// Две комнаты 3x3, разделенные стеной по x = 3.
+    fn two_rooms() -> WorldMap {
+        let map = WorldMap::new();
+        for y in 0..3 {
+            for x in 0..7 {
+                let tile = if x == 3 { WALL } else { FLOOR };
+                map.set_tile(WorldPos::new(x, y, 0), tile);
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn test_sealed_rooms_are_separate_components() {
+        let map = two_rooms();
+        let conn = Connectivity::compute(&map, WorldPos::new(0, 0, 0), WorldPos::new(6, 2, 0), &Direction::ORTHOGONAL);
+
+        assert_eq!(conn.count(), 2);
+        assert!(conn.is_connected(WorldPos::new(0, 0, 0), WorldPos::new(2, 2, 0)));
+        assert!(!conn.is_connected(WorldPos::new(0, 0, 0), WorldPos::new(4, 0, 0)));
+        assert_eq!(conn.component_at(WorldPos::new(3, 1, 0)), None); // Стена
+    }
+
+    #[test]
+    fn test_largest_and_disconnected() {
+        let map = two_rooms();
+        // Расширяем правую комнату
+        map.set_tile(WorldPos::new(7, 0, 0), FLOOR);
+
+        let conn = Connectivity::compute(&map, WorldPos::new(0, 0, 0), WorldPos::new(7, 2, 0), &Direction::ORTHOGONAL);
+        let largest = conn.largest().unwrap();
+
+        assert_eq!(conn.size(largest), 10);
+        assert_eq!(conn.component_at(WorldPos::new(7, 0, 0)), Some(largest));
+
+        let sealed: Vec<_> = conn.disconnected().collect();
+        assert_eq!(sealed.len(), 1);
+        assert_eq!(conn.tiles(sealed[0]).count(), 9);
+    }
+
+    #[test]
+    fn test_diagonal_connectivity() {
+        let map = WorldMap::new();
+        map.set_tile(WorldPos::new(0, 0, 0), FLOOR);
+        map.set_tile(WorldPos::new(1, 1, 0), FLOOR);
+
+        let (min, max) = (WorldPos::new(0, 0, 0), WorldPos::new(1, 1, 0));
+        assert_eq!(Connectivity::compute(&map, min, max, &Direction::ORTHOGONAL).count(), 2);
+        assert_eq!(Connectivity::compute(&map, min, max, &Direction::ALL_2D).count(), 1);
+    }
+}
@@ -0,0 +1,155 @@
+use std::ops::RangeInclusive;
+use cd_core::WorldPos;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DimensionId(pub u16);
+
+/// Измерение — непрерывный диапазон z внутри общего `WorldPos`.
+/// Overworld, инстансы подземелий, особые планы не пересекаются по z,
+/// поэтому все индексы, ключующиеся по z (карта, сетка, зоны), изолированы сами собой.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dimension {
+    pub id: DimensionId,
+    pub name: String,
+    /// Нижний этаж (включительно)
+    pub z_min: i32,
+    /// Верхний этаж (включительно)
+    pub z_max: i32,
+}
+
+impl Dimension {
+    pub fn z_range(&self) -> RangeInclusive<i32> {
+        self.z_min..=self.z_max
+    }
+
+    pub fn floors(&self) -> i32 {
+        self.z_max - self.z_min + 1
+    }
+
+    pub fn contains(&self, pos: WorldPos) -> bool {
+        self.z_range().contains(&pos.z())
+    }
+}
+
+/// Реестр измерений: резервирует диапазоны z.
+#[derive(Debug, Default)]
+pub struct DimensionRegistry {
+    // Отсортированы по z_min
+    dimensions: Vec<Dimension>,
+    next_id: u16,
+}
+
+impl DimensionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Зарезервировать конкретный диапазон z (например, overworld на z = 0).
+    /// `None`, если диапазон вне `WorldPos` или пересекается с уже занятым.
+    pub fn reserve(&mut self, name: impl Into<String>, z_min: i32, z_max: i32) -> Option<DimensionId> {
+        if z_min > z_max || z_min < WorldPos::Z_MIN || z_max > WorldPos::Z_MAX {
+            return None;
+        }
+        if self.dimensions.iter().any(|d| z_min <= d.z_max && d.z_min <= z_max) {
+            return None;
+        }
+
+        let id = DimensionId(self.next_id);
+        self.next_id = self.next_id.checked_add(1)?;
+
+        let dim = Dimension { id, name: name.into(), z_min, z_max };
+        let at = self.dimensions.partition_point(|d| d.z_min < z_min);
+        self.dimensions.insert(at, dim);
+        Some(id)
+    }
+
+    /// Выделить `floors` этажей в первом свободном промежутке снизу.
+    /// Используется для инстансов, которым не важно, где именно они лежат.
+    pub fn allocate(&mut self, name: impl Into<String>, floors: i32) -> Option<DimensionId> {
+        if floors <= 0 {
+            return None;
+        }
+
+        let mut start = WorldPos::Z_MIN;
+        for d in &self.dimensions {
+            if d.z_min - start >= floors {
+                break;
+            }
+            start = d.z_max + 1;
+        }
+        self.reserve(name, start, start + floors - 1)
+    }
+
+    /// Освободить диапазон. Данные карты в нем нужно выгрузить отдельно.
+    pub fn release(&mut self, id: DimensionId) -> Option<Dimension> {
+        let idx = self.dimensions.iter().position(|d| d.id == id)?;
+        Some(self.dimensions.remove(idx))
+    }
+
+    pub fn get(&self, id: DimensionId) -> Option<&Dimension> {
+        self.dimensions.iter().find(|d| d.id == id)
+    }
+
+    /// Измерение, которому принадлежит позиция.
+    pub fn dimension_at(&self, pos: WorldPos) -> Option<&Dimension> {
+        let z = pos.z();
+        let idx = self.dimensions.partition_point(|d| d.z_max < z);
+        self.dimensions.get(idx).filter(|d| d.z_min <= z)
+    }
+
+    /// Лежат ли две позиции в одном измерении.
+    pub fn same_dimension(&self, a: WorldPos, b: WorldPos) -> bool {
+        match (self.dimension_at(a), self.dimension_at(b)) {
+            (Some(da), Some(db)) => da.id == db.id,
+            _ => false,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Dimension> {
+        self.dimensions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dimensions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dimensions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_rejects_overlap() {
+        let mut dims = DimensionRegistry::new();
+        let overworld = dims.reserve("overworld", -8, 0).unwrap();
+
+        assert!(dims.reserve("clash", 0, 3).is_none());
+        assert!(dims.reserve("too_high", 2000, 3000).is_none());
+        let plane = dims.reserve("astral", 100, 100).unwrap();
+
+        assert_eq!(dims.dimension_at(WorldPos::new(5, 5, -3)).unwrap().id, overworld);
+        assert_eq!(dims.dimension_at(WorldPos::new(0, 0, 100)).unwrap().id, plane);
+        assert!(dims.dimension_at(WorldPos::new(0, 0, 50)).is_none());
+        assert!(!dims.same_dimension(WorldPos::new(0, 0, 0), WorldPos::new(0, 0, 100)));
+    }
+
+    #[test]
+    fn test_allocate_fills_gaps_and_reuses_released() {
+        let mut dims = DimensionRegistry::new();
+        dims.reserve("overworld", -8, 0).unwrap();
+
+        let a = dims.allocate("instance_a", 4).unwrap();
+        let b = dims.allocate("instance_b", 4).unwrap();
+        assert_eq!(dims.get(a).unwrap().z_range(), WorldPos::Z_MIN..=WorldPos::Z_MIN + 3);
+        assert_eq!(dims.get(b).unwrap().z_min, WorldPos::Z_MIN + 4);
+
+        dims.release(a).unwrap();
+        let c = dims.allocate("instance_c", 2).unwrap();
+        assert_eq!(dims.get(c).unwrap().z_min, WorldPos::Z_MIN);
+        assert_ne!(c, a); // id не переиспользуются
+    }
+}
@@ -1,14 +1,17 @@
 use cd_core::{ObjectGuid, WorldPos};
 use ahash::HashMap;
-use crate::CELL_SIZE;
+use crate::CELL_SHIFT;
 
 /// Пространственный индекс.
 /// Позволяет быстро отвечать на вопрос "кто находится в точке X,Y?".
 #[derive(Debug, Default)]
 pub struct SpatialGrid {
-    // Ключ - координаты ячейки (x / 16, y / 16)
+    // Ключ - координаты ячейки (x >> 4, y >> 4, z).
+    // z в ключе изолирует этажи и измерения друг от друга.
     // Значение - список ID сущностей
-    buckets: HashMap<(i32, i32), Vec<ObjectGuid>>,
+    buckets: HashMap<(i32, i32, i32), Vec<ObjectGuid>>,
+    // Точная позиция каждой сущности (для запросов "кто стоит на тайле")
+    positions: HashMap<ObjectGuid, WorldPos>,
 }
 
 impl SpatialGrid {
@@ -17,13 +20,16 @@ impl SpatialGrid {
     }
 
     /// Конвертирует мировые координаты в ключ ячейки
-    fn get_key(pos: WorldPos) -> (i32, i32) {
-        (pos.x() / CELL_SIZE, pos.y() / CELL_SIZE)
+    /// Сдвиг, а не деление: -1 / 16 == 0, а нужна ячейка -1.
+    fn get_key(pos: WorldPos) -> (i32, i32, i32) {
+        let (x, y, z) = pos.xyz();
+        (x >> CELL_SHIFT, y >> CELL_SHIFT, z)
     }
 
     pub fn insert(&mut self, entity: ObjectGuid, pos: WorldPos) {
         let key = Self::get_key(pos);
         self.buckets.entry(key).or_default().push(entity);
+        self.positions.insert(entity, pos);
     }
 
     pub fn remove(&mut self, entity: ObjectGuid, pos: WorldPos) {
@@ -32,6 +38,7 @@ impl SpatialGrid {
             // retain удаляет элементы, не удовлетворяющие условию
             list.retain(|&e| e != entity);
         }
+        self.positions.remove(&entity);
     }
 
     pub fn move_entity(&mut self, entity: ObjectGuid, old_pos: WorldPos, new_pos: WorldPos) {
@@ -39,6 +46,7 @@ impl SpatialGrid {
         let new_key = Self::get_key(new_pos);
 
         if old_key == new_key {
+            self.positions.insert(entity, new_pos);
             return; // Мы остались в той же ячейке сетки
         }
 
@@ -51,4 +59,57 @@ impl SpatialGrid {
         let key = Self::get_key(pos);
         self.buckets.get(&key).map(|v| v.as_slice()).unwrap_or(&[])
     }
-}
\ No newline at end of file
+
+    /// Сущности, стоящие ровно на тайле pos
+    pub fn entities_at(&self, pos: WorldPos) -> impl Iterator<Item = ObjectGuid> + '_ {
+        self.query_bucket(pos).iter().copied().filter(move |e| self.positions.get(e) == Some(&pos))
+    }
+
+    /// Последняя известная сетке позиция сущности
+    pub fn position_of(&self, entity: ObjectGuid) -> Option<WorldPos> {
+        self.positions.get(&entity).copied()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_are_isolated_by_z() {
+        let mut grid = SpatialGrid::new();
+        let (a, b) = (ObjectGuid::player(0, 0, 1), ObjectGuid::player(0, 0, 2));
+        grid.insert(a, WorldPos::new(3, 3, 0));
+        grid.insert(b, WorldPos::new(3, 3, -100));
+
+        assert_eq!(grid.query_bucket(WorldPos::new(3, 3, 0)), &[a]);
+        assert_eq!(grid.query_bucket(WorldPos::new(3, 3, -100)), &[b]);
+
+        grid.move_entity(a, WorldPos::new(3, 3, 0), WorldPos::new(3, 3, -100));
+        assert!(grid.query_bucket(WorldPos::new(3, 3, 0)).is_empty());
+        assert_eq!(grid.query_bucket(WorldPos::new(3, 3, -100)).len(), 2);
+    }
+
+    #[test]
+    fn test_negative_coords_use_floor_cells() {
+        let mut grid = SpatialGrid::new();
+        let a = ObjectGuid::player(0, 0, 1);
+        grid.insert(a, WorldPos::new(-1, -1, 0));
+
+        assert!(grid.query_bucket(WorldPos::new(0, 0, 0)).is_empty());
+        assert_eq!(grid.query_bucket(WorldPos::new(-16, -16, 0)), &[a]);
+    }
+
+    #[test]
+    fn test_entities_at_exact_tile() {
+        let mut grid = SpatialGrid::new();
+        let a = ObjectGuid::player(0, 0, 1);
+        grid.insert(a, WorldPos::new(2, 2, 0));
+
+        assert_eq!(grid.entities_at(WorldPos::new(2, 2, 0)).collect::<Vec<_>>(), vec![a]);
+        assert_eq!(grid.entities_at(WorldPos::new(3, 2, 0)).count(), 0); // Та же ячейка, другой тайл
+
+        grid.move_entity(a, WorldPos::new(2, 2, 0), WorldPos::new(3, 2, 0));
+        assert_eq!(grid.position_of(a), Some(WorldPos::new(3, 2, 0)));
+        assert_eq!(grid.entities_at(WorldPos::new(3, 2, 0)).count(), 1);
+    }
+}
@@ -3,16 +3,38 @@ pub mod chunk;
 pub mod grid; // Spatial Index
 pub mod world;
 pub mod region;
+pub mod connectivity;
+pub mod zone;
+pub mod tile_data;
+pub mod material;
+pub mod terrain;
+pub mod provider;
+pub mod stairs;
+pub mod step;
+pub mod dimension;
+pub mod portal;
+pub mod trace;
+pub mod worldgen;
 mod bitmask;
 mod sparse_chunk;
 mod shard;
+mod lock;
 
-pub use tile::{Tile, TileFlags};
+pub use tile::{MaterialID, Tile, TileFlags};
 pub use chunk::Chunk;
 pub use sparse_chunk::SparseChunk;
 pub use region::Region;
 pub use world::WorldMap;
 pub use grid::SpatialGrid;
+pub use dimension::{Dimension, DimensionId, DimensionRegistry};
+pub use connectivity::{Connectivity, ComponentId};
+pub use material::{MaterialDef, MaterialRegistry, TileState};
+pub use provider::{ChainProvider, ChunkGenerator, ChunkProvider, DiskProvider, GeneratorProvider};
+pub use step::CornerCutting;
+pub use terrain::DamageOutcome;
+pub use trace::TraceHit;
+pub use tile_data::{ChunkTileData, TileData, TrapParams};
+pub use zone::{Zone, ZoneId, ZoneKind, ZoneRegistry, ZoneShape, ZoneTransition};
 
 // Константы размера чанка
 pub const CHUNK_SIZE: i32 = 16;
@@ -20,9 +42,9 @@ pub const CHUNK_SHIFT: i32 = 4;
 pub const CHUNK_MASK: i32 = 15;
 pub const CHUNK_AREA: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
 
-// Размер ячейки сетки (Bucket).
-// 16 - совпадает с размером чанка. Это удобно для маппинга.
-const CELL_SIZE: i32 = CHUNK_SIZE;
+// Размер ячейки сетки (Bucket) как сдвиг: 1 << 4 = 16.
+// Совпадает с размером чанка. Это удобно для маппинга.
+const CELL_SHIFT: i32 = CHUNK_SHIFT;
 
 // 32x32 чанка = 1024 чанка в регионе.
 // 32 * 16 = 512 тайлов сторона региона
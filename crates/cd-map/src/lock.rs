@@ -0,0 +1,15 @@
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+// Паника другого потока под локом отравляет его. Худшее, что она могла оставить, —
+// недописанную запись одного чанка; это лучше, чем валить сервер на каждом
+// следующем обращении к карте. Поэтому отравленные локи просто открываем.
+
+/// `RwLock::read`, переживающий отравление.
+pub(crate) fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// `RwLock::write`, переживающий отравление.
+pub(crate) fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,164 @@
+use ahash::{HashMap, HashMapExt};
+use crate::{MaterialID, Tile, TileFlags};
+
+/// Одно состояние тайла материала (дверь: "closed" / "open").
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileState {
+    pub name: String,
+    pub flags: TileFlags,
+}
+
+impl TileState {
+    pub fn new(name: impl Into<String>, flags: TileFlags) -> Self {
+        Self { name: name.into(), flags }
+    }
+}
+
+/// Описание материала.
+/// Для интерактивных тайлов (двери, рычаги) `Tile::variant` хранит индекс текущего состояния,
+/// а флаги тайла всегда берутся из описания этого состояния.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterialDef {
+    pub id: MaterialID,
+    pub name: String,
+    /// Состояния по порядку. Interact переключает на следующее (по кругу).
+    pub states: Vec<TileState>,
+    /// Сколько урона выдерживает тайл. 0 — неразрушаемый.
+    pub hardness: u16,
+    /// Во что превращается разрушенный тайл (обломки, пол).
+    pub rubble: Option<Tile>,
+    /// Шанс (из 100) загореться от горящего соседа за тик. 0 — не горит.
+    pub flammability: u8,
+    /// Сколько тиков горит тайл.
+    pub fuel: u8,
+    /// Во что превращается сгоревший тайл (пепел, пол).
+    pub burns_to: Option<Tile>,
+}
+
+impl MaterialDef {
+    /// Материал с единственным состоянием (стена, пол).
+    pub fn simple(id: MaterialID, name: impl Into<String>, flags: TileFlags) -> Self {
+        Self::with_states(id, name, vec![TileState::new("default", flags)])
+    }
+
+    pub fn with_states(id: MaterialID, name: impl Into<String>, states: Vec<TileState>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            states,
+            hardness: 0,
+            rubble: None,
+            flammability: 0,
+            fuel: 0,
+            burns_to: None,
+        }
+    }
+
+    /// Делает материал разрушаемым.
+    pub fn destructible(mut self, hardness: u16, rubble: Tile) -> Self {
+        self.hardness = hardness;
+        self.rubble = Some(rubble);
+        self
+    }
+
+    pub fn is_destructible(&self) -> bool {
+        self.hardness > 0 && self.rubble.is_some()
+    }
+
+    /// Делает материал горючим.
+    pub fn flammable(mut self, flammability: u8, fuel: u8, burns_to: Tile) -> Self {
+        self.flammability = flammability;
+        self.fuel = fuel;
+        self.burns_to = Some(burns_to);
+        self
+    }
+
+    pub fn is_flammable(&self) -> bool {
+        self.flammability > 0 && self.fuel > 0
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.states.len() > 1
+    }
+
+    /// Тайл этого материала в заданном состоянии.
+    pub fn tile(&self, state: u8) -> Option<Tile> {
+        let def = self.states.get(state as usize)?;
+        Some(Tile { material: self.id, flags: def.flags, variant: state })
+    }
+
+    /// Тайл в состоянии с заданным именем.
+    pub fn tile_by_name(&self, state: &str) -> Option<Tile> {
+        let idx = self.states.iter().position(|s| s.name == state)?;
+        self.tile(idx as u8)
+    }
+
+    /// Имя состояния, в котором сейчас находится тайл.
+    pub fn state_name(&self, tile: Tile) -> Option<&str> {
+        self.states.get(tile.variant as usize).map(|s| s.name.as_str())
+    }
+
+    /// Следующее состояние тайла. `None`, если тайл не этого материала или не интерактивный.
+    pub fn next_state(&self, tile: Tile) -> Option<Tile> {
+        if tile.material != self.id || !self.is_interactive() {
+            return None;
+        }
+        let next = (tile.variant as usize + 1) % self.states.len();
+        self.tile(next as u8)
+    }
+}
+
+/// Реестр материалов: MaterialID -> описание.
+#[derive(Debug, Default)]
+pub struct MaterialRegistry {
+    defs: HashMap<MaterialID, MaterialDef>,
+}
+
+impl MaterialRegistry {
+    pub fn new() -> Self {
+        Self { defs: HashMap::new() }
+    }
+
+    pub fn register(&mut self, def: MaterialDef) {
+        self.defs.insert(def.id, def);
+    }
+
+    pub fn get(&self, id: MaterialID) -> Option<&MaterialDef> {
+        self.defs.get(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn door() -> MaterialDef {
+        MaterialDef::with_states(10, "Wooden Door", vec![
+            TileState::new("closed", TileFlags::SOLID | TileFlags::OPAQUE),
+            TileState::new("open", TileFlags::WALKABLE),
+        ])
+    }
+
+    #[test]
+    fn test_door_toggles_flags() {
+        let door = door();
+        let closed = door.tile_by_name("closed").unwrap();
+        assert!(closed.flags.contains(TileFlags::SOLID));
+
+        let open = door.next_state(closed).unwrap();
+        assert_eq!(door.state_name(open), Some("open"));
+        assert!(open.is_walkable());
+        assert!(!open.flags.contains(TileFlags::OPAQUE));
+
+        // По кругу обратно
+        assert_eq!(door.next_state(open), Some(closed));
+    }
+
+    #[test]
+    fn test_simple_material_is_not_interactive() {
+        let wall = MaterialDef::simple(1, "Stone Wall", TileFlags::SOLID);
+        let tile = wall.tile(0).unwrap();
+        assert_eq!(wall.next_state(tile), None);
+        assert_eq!(door().next_state(tile), None); // Чужой материал
+    }
+}
@@ -0,0 +1,44 @@
+use cd_core::WorldPos;
+use crate::{Tile, TileFlags, WorldMap};
+
+// Портал — тайл с флагом PORTAL и целью в TileData::portal.
+// Цель может лежать в любом измерении: для карты это просто другой z.
+
+impl WorldMap {
+    /// Куда ведет портал под `pos`. `None`, если это не портал или цель не задана.
+    pub fn portal_destination(&self, pos: WorldPos) -> Option<WorldPos> {
+        if !self.get_tile(pos).flags.contains(TileFlags::PORTAL) {
+            return None;
+        }
+        self.get_tile_data(pos)?.portal
+    }
+
+    /// Ставит портал в `pos`, ведущий в `dest`.
+    pub fn place_portal(&self, pos: WorldPos, tile: Tile, dest: WorldPos) {
+        // Сначала тайл: смена материала сбрасывает TileData
+        self.set_tile(pos, Tile { flags: tile.flags | TileFlags::PORTAL | TileFlags::WALKABLE, ..tile });
+        self.update_tile_data(pos, |data| data.portal = Some(dest));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GATE: Tile = Tile { material: 30, flags: TileFlags::NONE, variant: 0 };
+
+    #[test]
+    fn test_portal_leads_to_destination() {
+        let map = WorldMap::new();
+        let pos = WorldPos::new(10, 10, 0);
+        let dest = WorldPos::new(0, 0, -1000);
+
+        assert_eq!(map.portal_destination(pos), None);
+        map.place_portal(pos, GATE, dest);
+        assert_eq!(map.portal_destination(pos), Some(dest));
+
+        // Тайл заменили — портал исчез вместе с данными
+        map.set_tile(pos, Tile { material: 1, flags: TileFlags::WALKABLE, variant: 0 });
+        assert_eq!(map.portal_destination(pos), None);
+    }
+}
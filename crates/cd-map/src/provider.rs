@@ -0,0 +1,539 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use cd_core::{ObjectGuid, WorldPos};
+use crate::tile_data::{ChunkTileData, TileData, TrapParams};
+use crate::{Chunk, Region, CHUNK_SHIFT, CHUNK_SIZE, REGION_AREA, REGION_SHIFT, REGION_SIZE};
+
+/// Источник регионов для `WorldMap`.
+/// Вызывается один раз при первом обращении к региону, результат кэшируется в карте.
+pub trait ChunkProvider: Send + Sync {
+    /// Загрузить или сгенерировать регион. `Ok(None)` — региона нет, карта запомнит промах.
+    /// `Err` — регион есть, но прочитать его не удалось (битый файл, права).
+    fn provide_region(&self, region_key: WorldPos) -> io::Result<Option<Region>>;
+}
+
+/// Генератор отдельных чанков (детерминированный по seed внутри реализации).
+pub trait ChunkGenerator: Send + Sync {
+    /// `None` — чанк пустой (void), в регион не попадет.
+    fn generate_chunk(&self, chunk_key: WorldPos) -> Option<Chunk>;
+}
+
+/// Адаптер: собирает регион из чанков генератора.
+pub struct GeneratorProvider<G: ChunkGenerator> {
+    pub generator: G,
+}
+
+impl<G: ChunkGenerator> GeneratorProvider<G> {
+    pub fn new(generator: G) -> Self {
+        Self { generator }
+    }
+}
+
+impl<G: ChunkGenerator> ChunkProvider for GeneratorProvider<G> {
+    fn provide_region(&self, region_key: WorldPos) -> io::Result<Option<Region>> {
+        let (rx, ry, z) = region_key.xyz();
+        let mut region = Region::new();
+        let mut any = false;
+
+        for ly in 0..REGION_SIZE {
+            for lx in 0..REGION_SIZE {
+                let chunk_key = WorldPos::new((rx << REGION_SHIFT) + lx as i32, (ry << REGION_SHIFT) + ly as i32, z);
+                if let Some(chunk) = self.generator.generate_chunk(chunk_key) {
+                    *region.get_or_create_chunk(lx, ly) = chunk;
+                    any = true;
+                }
+            }
+        }
+
+        Ok(any.then_some(region))
+    }
+}
+
+/// Цепочка источников: первый, вернувший регион, побеждает.
+/// Ошибка обрывает цепочку: битый файл на диске не должен подменяться
+/// свежесгенерированным регионом. Типичная связка — диск, затем генератор.
+#[derive(Default)]
+pub struct ChainProvider {
+    providers: Vec<Box<dyn ChunkProvider>>,
+}
+
+impl ChainProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, provider: impl ChunkProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+}
+
+impl ChunkProvider for ChainProvider {
+    fn provide_region(&self, region_key: WorldPos) -> io::Result<Option<Region>> {
+        for provider in &self.providers {
+            if let Some(region) = provider.provide_region(region_key)? {
+                return Ok(Some(region));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Загрузчик регионов с диска. Один файл на регион.
+pub struct DiskProvider {
+    dir: PathBuf,
+}
+
+impl DiskProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn region_path(&self, region_key: WorldPos) -> PathBuf {
+        let (x, y, z) = region_key.xyz();
+        self.dir.join(format!("r.{}.{}.{}.cdr", x, y, z))
+    }
+
+    pub fn save_region(&self, region_key: WorldPos, region: &Region) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut w = BufWriter::new(File::create(self.region_path(region_key))?);
+        write_region(&mut w, region)?;
+        w.flush()
+    }
+
+    pub fn load_region(&self, region_key: WorldPos) -> io::Result<Region> {
+        let mut r = BufReader::new(File::open(self.region_path(region_key))?);
+        read_region(&mut r)
+    }
+
+    /// Как `load_region`, но отсутствие файла — `Ok(None)`.
+    /// Любая другая ошибка (битый файл, права) пробрасывается.
+    pub fn try_load_region(&self, region_key: WorldPos) -> io::Result<Option<Region>> {
+        match self.load_region(region_key) {
+            Ok(region) => Ok(Some(region)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl ChunkProvider for DiskProvider {
+    fn provide_region(&self, region_key: WorldPos) -> io::Result<Option<Region>> {
+        // Нет файла — не ошибка, просто регион еще не сохранялся
+        self.try_load_region(region_key).map_err(|e| {
+            io::Error::new(e.kind(), format!("{:?}: {}", self.region_path(region_key), e))
+        })
+    }
+}
+
+// --- Region File Format ---
+// [ MAGIC (4) | VERSION (1) | PRESENCE (16 x u64 LE) | CHUNKS... | TILE DATA (v2+) ]
+// Chunk: [ PALETTE_LEN (2, LE) | PALETTE (len x u32 LE) | INDICES (256) ]
+// Пишутся только присутствующие чанки, по порядку индексов.
+//
+// Tile Data: [ COUNT (2) | COUNT x (CHUNK_IDX (2) | ENTRIES (2) | ENTRY...) ]
+// Entry: [ LOCAL_IDX (1) | FIELDS (1) | поля из FIELDS по порядку битов ]
+//   text: LEN (2) + UTF-8 | lock_key: u32 | contents: LEN (2) + u64 GUID
+//   trap: kind u16, power i32, armed u8 | damage: u16 | portal: x, y, z i32
+// Все числа LE. Версия 1 (без данных тайлов) читается как есть.
+
+const REGION_MAGIC: &[u8; 4] = b"CDRG";
+const REGION_VERSION: u8 = 2;
+
+const FIELD_TEXT: u8 = 1 << 0;
+const FIELD_LOCK_KEY: u8 = 1 << 1;
+const FIELD_CONTENTS: u8 = 1 << 2;
+const FIELD_TRAP: u8 = 1 << 3;
+const FIELD_DAMAGE: u8 = 1 << 4;
+const FIELD_PORTAL: u8 = 1 << 5;
+const FIELD_ALL: u8 = (1 << 6) - 1;
+
+pub fn write_region(w: &mut impl Write, region: &Region) -> io::Result<()> {
+    w.write_all(REGION_MAGIC)?;
+    w.write_all(&[REGION_VERSION])?;
+    for block in region.presence_map {
+        w.write_all(&block.to_le_bytes())?;
+    }
+
+    for ry in 0..REGION_SIZE {
+        for rx in 0..REGION_SIZE {
+            let Some(chunk) = region.get_chunk(rx, ry) else { continue };
+            // palette_len = 0 означает "переполнился u8", т.е. все 256 записей
+            let len = match chunk.palette_len { 0 => 256, n => n as usize };
+            w.write_all(&(len as u16).to_le_bytes())?;
+            for packed in &chunk.palette[..len] {
+                w.write_all(&packed.to_le_bytes())?;
+            }
+            w.write_all(&chunk.indices)?;
+        }
+    }
+
+    let chunks: Vec<_> = region.tile_data().collect();
+    write_len(w, chunks.len())?;
+    for (rx, ry, data) in chunks {
+        w.write_all(&(((ry << REGION_SHIFT) | rx) as u16).to_le_bytes())?;
+        let mut entries: Vec<_> = data.iter().collect();
+        entries.sort_unstable_by_key(|&(lx, ly, _)| (ly, lx));
+        write_len(w, entries.len())?;
+        for (lx, ly, entry) in entries {
+            w.write_all(&[((ly << CHUNK_SHIFT) | lx) as u8])?;
+            write_tile_data(w, entry)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_tile_data(w: &mut impl Write, data: &TileData) -> io::Result<()> {
+    let mut fields = 0;
+    if data.text.is_some() { fields |= FIELD_TEXT; }
+    if data.lock_key.is_some() { fields |= FIELD_LOCK_KEY; }
+    if !data.contents.is_empty() { fields |= FIELD_CONTENTS; }
+    if data.trap.is_some() { fields |= FIELD_TRAP; }
+    if data.damage != 0 { fields |= FIELD_DAMAGE; }
+    if data.portal.is_some() { fields |= FIELD_PORTAL; }
+    w.write_all(&[fields])?;
+
+    if let Some(text) = &data.text {
+        write_len(w, text.len())?;
+        w.write_all(text.as_bytes())?;
+    }
+    if let Some(key) = data.lock_key {
+        w.write_all(&key.to_le_bytes())?;
+    }
+    if !data.contents.is_empty() {
+        write_len(w, data.contents.len())?;
+        for guid in &data.contents {
+            w.write_all(&guid.as_u64().to_le_bytes())?;
+        }
+    }
+    if let Some(trap) = data.trap {
+        w.write_all(&trap.kind.to_le_bytes())?;
+        w.write_all(&trap.power.to_le_bytes())?;
+        w.write_all(&[trap.armed as u8])?;
+    }
+    if data.damage != 0 {
+        w.write_all(&data.damage.to_le_bytes())?;
+    }
+    if let Some(portal) = data.portal {
+        let (x, y, z) = portal.xyz();
+        for v in [x, y, z] {
+            w.write_all(&v.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn write_len(w: &mut impl Write, len: usize) -> io::Result<()> {
+    let len = u16::try_from(len).map_err(|_| invalid("length does not fit u16"))?;
+    w.write_all(&len.to_le_bytes())
+}
+
+pub fn read_region(r: &mut impl Read) -> io::Result<Region> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    let mut version = [0u8; 1];
+    r.read_exact(&mut version)?;
+    if &magic != REGION_MAGIC || !(1..=REGION_VERSION).contains(&version[0]) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a region file"));
+    }
+
+    let mut presence = [0u64; REGION_AREA / 64];
+    let mut buf8 = [0u8; 8];
+    for block in presence.iter_mut() {
+        r.read_exact(&mut buf8)?;
+        *block = u64::from_le_bytes(buf8);
+    }
+
+    let mut region = Region::new();
+    for ry in 0..REGION_SIZE {
+        for rx in 0..REGION_SIZE {
+            let idx = (ry << REGION_SHIFT) | rx;
+            if presence[idx / 64] & (1 << (idx % 64)) == 0 {
+                continue;
+            }
+
+            let mut buf2 = [0u8; 2];
+            r.read_exact(&mut buf2)?;
+            let len = u16::from_le_bytes(buf2) as usize;
+            if len == 0 || len > 256 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "bad palette length"));
+            }
+
+            let chunk = region.get_or_create_chunk(rx, ry);
+            let mut buf4 = [0u8; 4];
+            for slot in chunk.palette.iter_mut().take(len) {
+                r.read_exact(&mut buf4)?;
+                *slot = u32::from_le_bytes(buf4);
+            }
+            chunk.palette_len = len as u8; // 256 -> 0, как и в рантайме
+            r.read_exact(&mut chunk.indices)?;
+
+            if chunk.indices.iter().any(|&i| i as usize >= len) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "palette index out of range"));
+            }
+            chunk.rebuild_masks();
+        }
+    }
+
+    if version[0] >= 2 {
+        for _ in 0..read_u16(r)? {
+            let idx = read_u16(r)? as usize;
+            if idx >= REGION_AREA {
+                return Err(invalid("chunk index out of range"));
+            }
+            let mut data = ChunkTileData::new();
+            for _ in 0..read_u16(r)? {
+                let local = read_u8(r)? as usize;
+                let lx = local & (CHUNK_SIZE as usize - 1);
+                let ly = local >> CHUNK_SHIFT;
+                data.set(lx, ly, read_tile_data(r)?);
+            }
+            region.set_chunk_tile_data(idx & (REGION_SIZE - 1), idx >> REGION_SHIFT, data);
+        }
+    }
+
+    Ok(region)
+}
+
+fn read_tile_data(r: &mut impl Read) -> io::Result<TileData> {
+    let fields = read_u8(r)?;
+    if fields & !FIELD_ALL != 0 {
+        return Err(invalid("unknown tile data fields"));
+    }
+
+    let mut data = TileData::default();
+    if fields & FIELD_TEXT != 0 {
+        let mut bytes = vec![0u8; read_u16(r)? as usize];
+        r.read_exact(&mut bytes)?;
+        data.text = Some(String::from_utf8(bytes).map_err(|_| invalid("tile text is not UTF-8"))?);
+    }
+    if fields & FIELD_LOCK_KEY != 0 {
+        data.lock_key = Some(read_u32(r)?);
+    }
+    if fields & FIELD_CONTENTS != 0 {
+        for _ in 0..read_u16(r)? {
+            data.contents.push(ObjectGuid::from_u64(read_u64(r)?));
+        }
+    }
+    if fields & FIELD_TRAP != 0 {
+        let kind = read_u16(r)?;
+        let power = read_u32(r)? as i32;
+        let armed = read_u8(r)? != 0;
+        data.trap = Some(TrapParams { kind, power, armed });
+    }
+    if fields & FIELD_DAMAGE != 0 {
+        data.damage = read_u16(r)?;
+    }
+    if fields & FIELD_PORTAL != 0 {
+        let (x, y, z) = (read_u32(r)? as i32, read_u32(r)? as i32, read_u32(r)? as i32);
+        data.portal = Some(WorldPos::try_new(x, y, z).map_err(|e| invalid(&e.to_string()))?);
+    }
+    Ok(data)
+}
+
+fn read_u8(r: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16(r: &mut impl Read) -> io::Result<u16> {
+    let mut buf = [0u8; 2];
+    r.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tile, TileFlags, WorldMap};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const STONE: Tile = Tile { material: 7, flags: TileFlags::SOLID, variant: 0 };
+
+    /// Генератор: каждый чанк — сплошной камень. Считает вызовы.
+    struct StoneGenerator(Arc<AtomicUsize>);
+
+    impl ChunkGenerator for StoneGenerator {
+        fn generate_chunk(&self, chunk_key: WorldPos) -> Option<Chunk> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            if chunk_key.z() != 0 {
+                return None; // Под землей пусто
+            }
+            let mut chunk = Chunk::new();
+            for ly in 0..16 {
+                for lx in 0..16 {
+                    chunk.set_tile(lx, ly, STONE);
+                }
+            }
+            Some(chunk)
+        }
+    }
+
+    #[test]
+    fn test_lazy_generation_is_cached() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let map = WorldMap::with_provider(GeneratorProvider::new(StoneGenerator(calls.clone())));
+
+        assert_eq!(map.get_tile(WorldPos::new(100_000, -50_000, 0)), STONE);
+        assert!(map.is_solid_fast(WorldPos::new(100_001, -50_000, 0)));
+        assert_eq!(calls.load(Ordering::Relaxed), crate::REGION_AREA); // Один регион
+
+        // Пустой уровень: провайдер вызван один раз, дальше промах кэширован
+        assert_eq!(map.get_tile(WorldPos::new(0, 0, -1)), Tile::default());
+        assert_eq!(map.get_tile(WorldPos::new(1, 0, -1)), Tile::default());
+        assert_eq!(calls.load(Ordering::Relaxed), crate::REGION_AREA * 2);
+    }
+
+    #[test]
+    fn test_region_file_roundtrip() {
+        let mut region = Region::new();
+        region.get_or_create_chunk(3, 4).set_tile(1, 2, STONE);
+        region.get_or_create_chunk(31, 31).set_tile(15, 15, Tile { material: 9, ..Default::default() });
+
+        let mut bytes = Vec::new();
+        write_region(&mut bytes, &region).unwrap();
+        let loaded = read_region(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(loaded.presence_map, region.presence_map);
+        let chunk = loaded.get_chunk(3, 4).unwrap();
+        assert_eq!(chunk.get_tile(1, 2), STONE);
+        assert!(chunk.is_solid_local(1, 2));
+        assert_eq!(loaded.get_chunk(31, 31).unwrap().get_tile(15, 15).material, 9);
+        assert!(loaded.get_chunk(0, 0).is_none());
+
+        assert!(read_region(&mut &bytes[..10]).is_err());
+
+        // Файл версии 1: без секции данных тайлов
+        let mut v1 = bytes[..bytes.len() - 2].to_vec();
+        v1[4] = 1;
+        assert_eq!(read_region(&mut v1.as_slice()).unwrap().presence_map, region.presence_map);
+    }
+
+    #[test]
+    fn test_region_file_keeps_tile_data() {
+        let mut data = ChunkTileData::new();
+        data.set(1, 2, TileData { text: Some("Привет".into()), lock_key: Some(7), damage: 3, ..Default::default() });
+        data.set(15, 15, TileData {
+            contents: vec![ObjectGuid::item(1, 2, 3)],
+            trap: Some(TrapParams { kind: 4, power: -5, armed: true }),
+            portal: Some(WorldPos::new(-10, 20, -1)),
+            ..Default::default()
+        });
+
+        let mut region = Region::new();
+        region.get_or_create_chunk(3, 4).set_tile(1, 2, STONE);
+        region.set_chunk_tile_data(3, 4, data.clone());
+        // Данные тайлов бывают и у чанка без статики (тайл только в дельте)
+        region.set_chunk_tile_data(0, 31, data.clone());
+
+        let mut bytes = Vec::new();
+        write_region(&mut bytes, &region).unwrap();
+        let loaded = read_region(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded.chunk_tile_data(3, 4), Some(&data));
+        assert_eq!(loaded.chunk_tile_data(0, 31), Some(&data));
+        assert_eq!(loaded.chunk_tile_data(0, 0), None);
+    }
+
+    #[test]
+    fn test_tile_data_survives_unload_and_disk() {
+        let dir = std::env::temp_dir().join(format!("cd-map-tile-data-{}", std::process::id()));
+        let pos = WorldPos::new(40, -3, 0);
+        let region_key = pos.chunk_key().region_key();
+        let chest = TileData { lock_key: Some(42), contents: vec![ObjectGuid::item(0, 0, 9)], ..Default::default() };
+
+        let world = WorldMap::with_provider(DiskProvider::new(&dir));
+        world.put_chunk(pos.chunk_key(), Chunk::new());
+        world.set_tile_data(pos, chest.clone());
+
+        let region = world.unload_region(region_key).unwrap();
+        assert_eq!(world.get_tile_data(pos), None); // Уехали вместе с регионом
+        DiskProvider::new(&dir).save_region(region_key, &region).unwrap();
+
+        // Свежая карта: регион и его данные подгружаются с диска при первом обращении
+        let restored = WorldMap::with_provider(DiskProvider::new(&dir));
+        let _ = restored.get_tile(pos);
+        assert_eq!(restored.get_tile_data(pos), Some(chest));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_disk_provider_missing_vs_corrupt() {
+        let dir = std::env::temp_dir().join(format!("cd-map-provider-{}", std::process::id()));
+        let disk = DiskProvider::new(&dir);
+        let key = WorldPos::new(0, 0, 0);
+
+        // Файла нет — просто пусто
+        assert!(disk.try_load_region(key).unwrap().is_none());
+        assert!(disk.provide_region(key).unwrap().is_none());
+
+        // Битый файл — ошибка, а не "пустой регион"
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(disk.region_path(key), b"CDRG\x01garbage").unwrap();
+        let Err(err) = disk.try_load_region(key) else { panic!("corrupt region must fail") };
+        assert_ne!(err.kind(), io::ErrorKind::NotFound);
+        assert!(disk.provide_region(key).is_err());
+
+        let mut region = Region::new();
+        region.get_or_create_chunk(0, 0).set_tile(1, 1, STONE);
+        disk.save_region(key, &region).unwrap();
+        assert!(disk.try_load_region(key).unwrap().is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_region_is_not_replaced_by_generator() {
+        let dir = std::env::temp_dir().join(format!("cd-map-provider-chain-{}", std::process::id()));
+        let key = WorldPos::new(0, 0, 0);
+        let path = DiskProvider::new(&dir).region_path(key);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, b"CDRG\x02garbage").unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let chain = || {
+            ChainProvider::new()
+                .with(DiskProvider::new(&dir))
+                .with(GeneratorProvider::new(StoneGenerator(calls.clone())))
+        };
+
+        // Ошибка диска не проваливается в генератор
+        assert!(chain().provide_region(key).is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+
+        // Карта не подставляет камень вместо битого региона, файл не тронут
+        let map = WorldMap::with_provider(chain());
+        assert_eq!(map.get_tile(WorldPos::new(1, 1, 0)), Tile::default());
+        assert!(!map.is_solid_fast(WorldPos::new(2, 2, 0)));
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+        assert_eq!(std::fs::read(&path).unwrap(), b"CDRG\x02garbage");
+
+        // Соседний регион без файла генерируется как обычно
+        assert_eq!(map.get_tile(WorldPos::new(1000, 0, 0)), STONE);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,8 @@
+use ahash::{HashMap, HashMapExt};
 use bitflags::bitflags;
 use crate::chunk::Chunk;
-use crate::{REGION_AREA, REGION_SHIFT};
+use crate::tile_data::ChunkTileData;
+use crate::{REGION_AREA, REGION_SHIFT, REGION_SIZE};
 
 bitflags! {
     /// Маска для быстрого определения, загружен ли чанк внутри региона.
@@ -12,36 +14,52 @@ bitflags! {
 
 /// Регион — это крупный статический блок карты.
 /// Используется для стриминга с диска.
+///
+/// С фичей `rkyv` регион архивируется целиком: файл можно отобразить в память,
+/// проверить `rkyv::check_archived_root` и читать чанки из `ArchivedRegion` на месте.
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
 pub struct Region {
     // Линеаризованный массив чанков.
+    #[cfg_attr(feature = "rkyv", with(archive::RegionChunks))]
     chunks: Box<[Chunk; REGION_AREA]>,
 
     // Битовая маска, указывающая, инициализирован ли чанк реальными данными.
     pub presence_map: [u64; REGION_AREA / 64],
+
+    // Данные тайлов по индексу чанка. В рантайме живут в шардах WorldMap,
+    // здесь — только пока регион лежит на диске / в пути (unload -> save -> load).
+    // В архиве — отсортированный список (индекс чанка, записи тайлов).
+    #[cfg_attr(feature = "rkyv", with(archive::RegionTileData))]
+    tile_data: HashMap<usize, ChunkTileData>,
 }
 
 impl Default for Region {
     fn default() -> Self {
-        let mut vec = Vec::with_capacity(REGION_AREA);
-        for _ in 0..REGION_AREA {
-            vec.push(Chunk::default());
-        }
-
-        // Превращаем Vec<Chunk> в Box<[Chunk]>
-        let boxed_slice: Box<[Chunk]> = vec.into_boxed_slice();
-
-        // Превращаем Box<[Chunk]> в Box<[Chunk; 1024]>
-        // unsafe здесь нужен, так как try_into для больших массивов может быть не оптимизирован,
-        // но standard library `try_into()` работает безопасно.
-        let chunks = boxed_slice.try_into().map_err(|_| "Allocation error").unwrap();
-
         Self {
-            chunks,
+            chunks: empty_chunks(),
             presence_map: [0; REGION_AREA / 64],
+            tile_data: HashMap::new(),
         }
     }
 }
 
+/// Массив пустых чанков сразу в куче: на стеке 1024 чанка не помещаются.
+fn empty_chunks() -> Box<[Chunk; REGION_AREA]> {
+    let mut vec = Vec::with_capacity(REGION_AREA);
+    for _ in 0..REGION_AREA {
+        vec.push(Chunk::default());
+    }
+
+    // Превращаем Vec<Chunk> в Box<[Chunk]>
+    let boxed_slice: Box<[Chunk]> = vec.into_boxed_slice();
+
+    // Превращаем Box<[Chunk]> в Box<[Chunk; 1024]>
+    // unsafe здесь нужен, так как try_into для больших массивов может быть не оптимизирован,
+    // но standard library `try_into()` работает безопасно.
+    boxed_slice.try_into().map_err(|_| "Allocation error").unwrap()
+}
+
 impl Region {
     pub fn new() -> Self {
         Self::default()
@@ -80,6 +98,34 @@ impl Region {
         unsafe { self.chunks.get_unchecked_mut(idx) }
     }
 
+    pub fn chunk_tile_data(&self, rx: usize, ry: usize) -> Option<&ChunkTileData> {
+        self.tile_data.get(&((ry << REGION_SHIFT) | rx))
+    }
+
+    /// Пустые данные не храним.
+    pub fn set_chunk_tile_data(&mut self, rx: usize, ry: usize, data: ChunkTileData) {
+        let idx = (ry << REGION_SHIFT) | rx;
+        if data.is_empty() {
+            self.tile_data.remove(&idx);
+        } else {
+            self.tile_data.insert(idx, data);
+        }
+    }
+
+    /// Итерация по (rx, ry, data) в порядке индексов чанков.
+    pub fn tile_data(&self) -> impl Iterator<Item = (usize, usize, &ChunkTileData)> {
+        let mut entries: Vec<_> = self.tile_data.iter().collect();
+        entries.sort_unstable_by_key(|(idx, _)| **idx);
+        entries.into_iter().map(|(&idx, data)| (idx & (REGION_SIZE - 1), idx >> REGION_SHIFT, data))
+    }
+
+    /// Забрать данные тайлов (при загрузке региона в WorldMap).
+    pub fn take_tile_data(&mut self) -> impl Iterator<Item = (usize, usize, ChunkTileData)> + use<> {
+        std::mem::take(&mut self.tile_data)
+            .into_iter()
+            .map(|(idx, data)| (idx & (REGION_SIZE - 1), idx >> REGION_SHIFT, data))
+    }
+
     #[inline]
     fn check_presence(&self, idx: usize) -> bool {
         let block = idx / 64;
@@ -97,3 +143,204 @@ impl Region {
         }
     }
 }
+
+#[cfg(feature = "rkyv")]
+impl ArchivedRegion {
+    /// Чанк прямо из архива (None, если чанк не присутствует).
+    pub fn get_chunk(&self, rx: usize, ry: usize) -> Option<&crate::chunk::ArchivedChunk> {
+        let idx = (ry << REGION_SHIFT) | rx;
+        let present = self.presence_map.get(idx / 64).is_some_and(|b| b & (1u64 << (idx % 64)) != 0);
+        if !present {
+            return None;
+        }
+        self.chunks.get().get(idx)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+#[allow(unsafe_code)]
+mod archive {
+    use super::{empty_chunks, Chunk, ChunkTileData, HashMap, HashMapExt, REGION_AREA};
+    use crate::tile_data::TileData;
+    use crate::CHUNK_SHIFT;
+    use rkyv::ser::{ScratchSpace, Serializer};
+    use rkyv::vec::{ArchivedVec, VecResolver};
+    use rkyv::with::{ArchiveWith, DeserializeWith, SerializeWith};
+    use rkyv::{Archive, Archived, Deserialize, Fallible, Resolver, Serialize};
+
+    type Chunks = Box<[Chunk; REGION_AREA]>;
+
+    /// Архивирует чанки как обычный Box, но при десериализации собирает их
+    /// сразу в куче — стандартная реализация прошла бы через стек (~1.3 МБ).
+    pub struct RegionChunks;
+
+    impl ArchiveWith<Chunks> for RegionChunks {
+        type Archived = Archived<Chunks>;
+        type Resolver = Resolver<Chunks>;
+
+        #[inline]
+        unsafe fn resolve_with(field: &Chunks, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+            // Safety: контракт тот же, что у Box::resolve
+            unsafe { field.resolve(pos, resolver, out) }
+        }
+    }
+
+    impl<S: Serializer + ?Sized> SerializeWith<Chunks, S> for RegionChunks {
+        fn serialize_with(field: &Chunks, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+            field.serialize(serializer)
+        }
+    }
+
+    impl<D: Fallible + ?Sized> DeserializeWith<Archived<Chunks>, Chunks, D> for RegionChunks {
+        fn deserialize_with(field: &Archived<Chunks>, deserializer: &mut D) -> Result<Chunks, D::Error> {
+            let mut chunks = empty_chunks();
+            for (dst, src) in chunks.iter_mut().zip(field.iter()) {
+                *dst = src.deserialize(deserializer)?;
+            }
+            Ok(chunks)
+        }
+    }
+
+    /// Данные одного тайла: (ly << 4 | lx) и сами данные.
+    #[derive(Archive, Serialize, Deserialize)]
+    #[archive(check_bytes)]
+    pub struct TileEntry {
+        pub idx: u8,
+        pub data: TileData,
+    }
+
+    /// Данные тайлов одного чанка региона.
+    #[derive(Archive, Serialize, Deserialize)]
+    #[archive(check_bytes)]
+    pub struct ChunkEntry {
+        pub idx: u16,
+        pub tiles: Vec<TileEntry>,
+    }
+
+    type TileDataMap = HashMap<usize, ChunkTileData>;
+
+    /// Архивирует данные тайлов списком, отсортированным по индексам:
+    /// одинаковый регион всегда дает одинаковые байты.
+    pub struct RegionTileData;
+
+    fn sorted_entries(field: &TileDataMap) -> Vec<ChunkEntry> {
+        let mut chunks: Vec<ChunkEntry> = field
+            .iter()
+            .map(|(&idx, data)| {
+                let mut tiles: Vec<TileEntry> = data
+                    .iter()
+                    .map(|(lx, ly, data)| TileEntry { idx: ((ly << CHUNK_SHIFT) | lx) as u8, data: data.clone() })
+                    .collect();
+                tiles.sort_unstable_by_key(|t| t.idx);
+                ChunkEntry { idx: idx as u16, tiles }
+            })
+            .collect();
+        chunks.sort_unstable_by_key(|c| c.idx);
+        chunks
+    }
+
+    impl ArchiveWith<TileDataMap> for RegionTileData {
+        type Archived = ArchivedVec<ArchivedChunkEntry>;
+        type Resolver = VecResolver;
+
+        #[inline]
+        unsafe fn resolve_with(field: &TileDataMap, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+            // Safety: контракт тот же, что у Vec::resolve; длина совпадает с сериализованной
+            unsafe { ArchivedVec::resolve_from_len(field.len(), pos, resolver, out) }
+        }
+    }
+
+    impl<S: ScratchSpace + Serializer + ?Sized> SerializeWith<TileDataMap, S> for RegionTileData {
+        fn serialize_with(field: &TileDataMap, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+            ArchivedVec::serialize_from_iter::<ChunkEntry, _, _, _>(sorted_entries(field).into_iter(), serializer)
+        }
+    }
+
+    impl<D: Fallible + ?Sized> DeserializeWith<ArchivedVec<ArchivedChunkEntry>, TileDataMap, D> for RegionTileData {
+        fn deserialize_with(field: &ArchivedVec<ArchivedChunkEntry>, deserializer: &mut D) -> Result<TileDataMap, D::Error> {
+            let mut map = HashMap::with_capacity(field.len());
+            for chunk in field.iter() {
+                let chunk: ChunkEntry = chunk.deserialize(deserializer)?;
+                let mut data = ChunkTileData::new();
+                for tile in chunk.tiles {
+                    let idx = tile.idx as usize;
+                    data.set(idx & ((1 << CHUNK_SHIFT) - 1), idx >> CHUNK_SHIFT, tile.data);
+                }
+                if !data.is_empty() {
+                    map.insert(chunk.idx as usize, data);
+                }
+            }
+            Ok(map)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rkyv"))]
+mod tests {
+    use super::*;
+    use crate::{Tile, TileFlags};
+
+    #[test]
+    fn test_region_archive_roundtrip() {
+        let wall = Tile { material: 7, flags: TileFlags::SOLID | TileFlags::OPAQUE, variant: 3 };
+        let mut region = Region::new();
+        region.get_or_create_chunk(3, 5).set_tile(2, 9, wall);
+
+        let bytes = rkyv::to_bytes::<_, 4096>(&region).unwrap();
+        let archived = rkyv::check_archived_root::<Region>(&bytes).unwrap();
+
+        // Чтение на месте
+        let chunk = archived.get_chunk(3, 5).unwrap();
+        assert_eq!(chunk.get_tile(2, 9), wall);
+        assert!(chunk.is_solid_local(2, 9));
+        assert!(!chunk.is_solid_local(0, 0));
+        assert!(archived.get_chunk(0, 0).is_none());
+
+        // Полная десериализация
+        let restored: Region = rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible).unwrap();
+        assert_eq!(restored.get_chunk(3, 5).unwrap().get_tile(2, 9), wall);
+        assert!(restored.get_chunk(0, 0).is_none());
+
+        // Испорченный архив не проходит валидацию
+        let mut broken = bytes.to_vec();
+        let len = broken.len();
+        broken[len - 8..].fill(0xFF);
+        assert!(rkyv::check_archived_root::<Region>(&broken).is_err());
+    }
+
+    #[test]
+    fn test_region_archive_keeps_tile_data() {
+        use crate::tile_data::{ChunkTileData, TileData, TrapParams};
+        use cd_core::{ObjectGuid, WorldPos};
+
+        let mut sign = ChunkTileData::new();
+        sign.set(1, 2, TileData { text: Some("Beware".into()), ..Default::default() });
+        sign.set(15, 15, TileData {
+            lock_key: Some(7),
+            contents: vec![ObjectGuid::from_u64(42)],
+            trap: Some(TrapParams { kind: 3, power: -5, armed: true }),
+            damage: 9,
+            portal: Some(WorldPos::new(-100, 200, -3)),
+            ..Default::default()
+        });
+        let mut door = ChunkTileData::new();
+        door.set(0, 0, TileData { lock_key: Some(1), ..Default::default() });
+
+        let mut region = Region::new();
+        region.get_or_create_chunk(3, 5);
+        region.set_chunk_tile_data(3, 5, sign.clone());
+        region.set_chunk_tile_data(31, 31, door.clone());
+
+        let bytes = rkyv::to_bytes::<_, 4096>(&region).unwrap();
+        let archived = rkyv::check_archived_root::<Region>(&bytes).unwrap();
+        let restored: Region = rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible).unwrap();
+
+        assert_eq!(restored.chunk_tile_data(3, 5), Some(&sign));
+        assert_eq!(restored.chunk_tile_data(31, 31), Some(&door));
+        assert_eq!(restored.tile_data().count(), 2);
+
+        // Порядок записи не зависит от порядка обхода HashMap
+        let again = rkyv::to_bytes::<_, 4096>(&restored).unwrap();
+        assert_eq!(bytes.as_slice(), again.as_slice());
+    }
+}
@@ -1,4 +1,6 @@
 use crate::sparse_chunk::SparseChunk;
+use crate::tile_data::{ChunkTileData, TileData};
+use crate::lock;
 use crate::{CHUNK_SHIFT, Chunk, Tile};
 use ahash::{HashMap, HashMapExt};
 use cd_core::WorldPos;
@@ -7,12 +9,16 @@ use std::sync::RwLock;
 pub struct Shard {
     // RwLock внутри шарда защищает только данные этого шарда
     deltas: RwLock<HashMap<WorldPos, SparseChunk>>,
+    // Данные тайлов (сундуки, таблички). Живут отдельно от дельт,
+    // поэтому переживают запекание дельты в статику.
+    tile_data: RwLock<HashMap<WorldPos, ChunkTileData>>,
 }
 
 impl Shard {
     pub(crate) fn new() -> Self {
         Self {
             deltas: RwLock::new(HashMap::new()),
+            tile_data: RwLock::new(HashMap::new()),
         }
     }
 
@@ -67,4 +73,80 @@ impl Shard {
 
         delta.set(lx, ly, tile);
     }
+
+    // --- Tile Data ---
+
+    pub(crate) fn get_tile_data(&self, chunk_key: WorldPos, lx: usize, ly: usize) -> Option<TileData> {
+        let guard = lock::read(&self.tile_data);
+        guard.get(&chunk_key).and_then(|c| c.get(lx, ly)).cloned()
+    }
+
+    /// Изменяет данные тайла "на месте". Пустые после изменения данные удаляются.
+    pub(crate) fn update_tile_data<R>(
+        &self,
+        chunk_key: WorldPos,
+        lx: usize,
+        ly: usize,
+        f: impl FnOnce(&mut TileData) -> R,
+    ) -> R {
+        let mut guard = lock::write(&self.tile_data);
+        let chunk = guard.entry(chunk_key).or_default();
+
+        let mut data = chunk.remove(lx, ly).unwrap_or_default();
+        let result = f(&mut data);
+        chunk.set(lx, ly, data);
+
+        if chunk.is_empty() {
+            guard.remove(&chunk_key);
+        }
+        result
+    }
+
+    pub(crate) fn remove_tile_data(&self, chunk_key: WorldPos, lx: usize, ly: usize) -> Option<TileData> {
+        let mut guard = lock::write(&self.tile_data);
+        let chunk = guard.get_mut(&chunk_key)?;
+        let removed = chunk.remove(lx, ly);
+        if chunk.is_empty() {
+            guard.remove(&chunk_key);
+        }
+        removed
+    }
+
+    pub(crate) fn chunk_tile_data(&self, chunk_key: WorldPos) -> Option<ChunkTileData> {
+        lock::read(&self.tile_data).get(&chunk_key).cloned()
+    }
+
+    pub(crate) fn put_chunk_tile_data(&self, chunk_key: WorldPos, data: ChunkTileData) {
+        let mut guard = lock::write(&self.tile_data);
+        if data.is_empty() {
+            guard.remove(&chunk_key);
+        } else {
+            guard.insert(chunk_key, data);
+        }
+    }
+
+    pub(crate) fn take_chunk_tile_data(&self, chunk_key: WorldPos) -> Option<ChunkTileData> {
+        lock::write(&self.tile_data).remove(&chunk_key)
+    }
+
+    /// Добавить загруженные данные, не затирая уже существующие записи
+    /// (их могли выставить до того, как регион подгрузился).
+    pub(crate) fn merge_chunk_tile_data(&self, chunk_key: WorldPos, data: ChunkTileData) {
+        if data.is_empty() {
+            return;
+        }
+        let mut guard = lock::write(&self.tile_data);
+        let current = guard.entry(chunk_key).or_default();
+        for (lx, ly, entry) in data.iter() {
+            if current.get(lx, ly).is_none() {
+                current.set(lx, ly, entry.clone());
+            }
+        }
+    }
+
+    /// Удалить дельты и данные тайлов всех чанков, для которых `f` вернул false.
+    pub(crate) fn retain_chunks(&self, f: impl Fn(WorldPos) -> bool) {
+        lock::write(&self.deltas).retain(|&key, _| f(key));
+        lock::write(&self.tile_data).retain(|&key, _| f(key));
+    }
 }
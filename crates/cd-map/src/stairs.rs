@@ -0,0 +1,67 @@
+use cd_core::{Direction, GridLogic, WorldPos};
+use crate::{Tile, TileFlags, WorldMap};
+
+// Лестницы связаны по вертикали: STAIRS_UP в (x, y, z) ведет на STAIRS_DOWN в (x, y, z + 1).
+// Отдельная таблица связей не нужна — пара определяется координатами.
+
+impl WorldMap {
+    /// Куда ведет лестница под `pos` при движении `Up`/`Down`.
+    /// `None`, если под ногами нет лестницы нужного типа или на другом конце нет парной.
+    pub fn stair_destination(&self, pos: WorldPos, dir: Direction) -> Option<WorldPos> {
+        let (here, there) = match dir {
+            Direction::Up => (TileFlags::STAIRS_UP, TileFlags::STAIRS_DOWN),
+            Direction::Down => (TileFlags::STAIRS_DOWN, TileFlags::STAIRS_UP),
+            _ => return None,
+        };
+
+        if !self.get_tile(pos).flags.contains(here) {
+            return None;
+        }
+
+        let dest = pos.shift(dir);
+        let tile = self.get_tile(dest);
+        (tile.flags.contains(there) && tile.is_walkable()).then_some(dest)
+    }
+
+    /// Ставит парную лестницу: `lower` получает подъем, тайл над ним — спуск.
+    /// Флаги лестниц и проходимости добавляются к флагам переданных тайлов.
+    pub fn place_stairs(&self, lower: WorldPos, up: Tile, down: Tile) {
+        let upper = lower.shift(Direction::Up);
+        self.set_tile(lower, Tile { flags: up.flags | TileFlags::STAIRS_UP | TileFlags::WALKABLE, ..up });
+        self.set_tile(upper, Tile { flags: down.flags | TileFlags::STAIRS_DOWN | TileFlags::WALKABLE, ..down });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAIRS: Tile = Tile { material: 20, flags: TileFlags::NONE, variant: 0 };
+    const FLOOR: Tile = Tile { material: 1, flags: TileFlags::WALKABLE, variant: 0 };
+
+    #[test]
+    fn test_stairs_link_neighbouring_floors() {
+        let map = WorldMap::new();
+        let lower = WorldPos::new(5, 5, -2);
+        map.place_stairs(lower, STAIRS, STAIRS);
+
+        let upper = WorldPos::new(5, 5, -1);
+        assert_eq!(map.stair_destination(lower, Direction::Up), Some(upper));
+        assert_eq!(map.stair_destination(upper, Direction::Down), Some(lower));
+
+        // Вниз с нижней лестницы и вверх с верхней — нельзя
+        assert_eq!(map.stair_destination(lower, Direction::Down), None);
+        assert_eq!(map.stair_destination(upper, Direction::Up), None);
+        assert_eq!(map.stair_destination(lower, Direction::North), None);
+    }
+
+    #[test]
+    fn test_unpaired_stairs_lead_nowhere() {
+        let map = WorldMap::new();
+        let pos = WorldPos::new(0, 0, 0);
+        map.set_tile(pos, Tile { flags: TileFlags::WALKABLE | TileFlags::STAIRS_UP, ..STAIRS });
+        map.set_tile(pos.shift(Direction::Up), FLOOR);
+
+        assert_eq!(map.stair_destination(pos, Direction::Up), None);
+    }
+}
@@ -0,0 +1,64 @@
+use cd_core::{Direction, GridLogic, WorldPos};
+use crate::WorldMap;
+
+/// Правило срезания углов при диагональном шаге.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CornerCutting {
+    /// Углы не мешают: важна только целевая клетка.
+    Allow,
+    /// Достаточно одной свободной ортогональной клетки (протиснуться мимо угла).
+    OneSide,
+    /// Обе ортогональные клетки должны быть свободны — нельзя пройти сквозь стык стен.
+    #[default]
+    Never,
+}
+
+impl WorldMap {
+    /// Можно ли шагнуть из `from` в направлении `dir`.
+    /// Горизонталь проверяется по маскам solid, вертикаль — по лестницам.
+    pub fn can_step(&self, from: WorldPos, dir: Direction, corners: CornerCutting) -> bool {
+        match dir {
+            Direction::None => true,
+            Direction::Up | Direction::Down => self.stair_destination(from, dir).is_some(),
+            _ => {
+                if self.is_solid_fast(from.shift(dir)) {
+                    return false;
+                }
+                let Some((a, b)) = dir.components() else { return true };
+                let (a_free, b_free) = (!self.is_solid_fast(from.shift(a)), !self.is_solid_fast(from.shift(b)));
+                match corners {
+                    CornerCutting::Allow => true,
+                    CornerCutting::OneSide => a_free || b_free,
+                    CornerCutting::Never => a_free && b_free,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tile, TileFlags};
+
+    const WALL: Tile = Tile { material: 1, flags: TileFlags::SOLID, variant: 0 };
+
+    #[test]
+    fn test_diagonal_corner_rules() {
+        let map = WorldMap::new();
+        let from = WorldPos::new(0, 0, 0);
+        map.set_tile(WorldPos::new(1, 0, 0), WALL); // Восток закрыт
+
+        assert!(!map.can_step(from, Direction::East, CornerCutting::Allow));
+        assert!(map.can_step(from, Direction::SouthEast, CornerCutting::Allow));
+        assert!(map.can_step(from, Direction::SouthEast, CornerCutting::OneSide));
+        assert!(!map.can_step(from, Direction::SouthEast, CornerCutting::Never));
+
+        map.set_tile(WorldPos::new(0, 1, 0), WALL); // И юг — щель между стенами
+        assert!(map.can_step(from, Direction::SouthEast, CornerCutting::Allow));
+        assert!(!map.can_step(from, Direction::SouthEast, CornerCutting::OneSide));
+
+        assert!(map.can_step(from, Direction::NorthWest, CornerCutting::Never));
+        assert!(!map.can_step(from, Direction::Up, CornerCutting::Never)); // Нет лестницы
+    }
+}
@@ -0,0 +1,88 @@
+use cd_core::WorldPos;
+use crate::{MaterialRegistry, Tile, WorldMap};
+
+/// Результат нанесения урона тайлу.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DamageOutcome {
+    /// Материал неразрушаемый (или неизвестный)
+    Immune,
+    /// Тайл поврежден, но держится
+    Damaged { damage: u16, hardness: u16 },
+    /// Тайл разрушен и заменен обломками
+    Destroyed { rubble: Tile },
+}
+
+impl WorldMap {
+    /// Наносит урон тайлу. Урон копится в данных тайла;
+    /// как только он достигает твердости материала, тайл заменяется на `rubble`
+    /// (а его данные удаляются вместе со старым материалом).
+    pub fn damage_tile(&self, pos: WorldPos, amount: u16, materials: &MaterialRegistry) -> DamageOutcome {
+        let tile = self.get_tile(pos);
+        let Some((hardness, Some(rubble))) = materials
+            .get(tile.material)
+            .filter(|def| def.is_destructible())
+            .map(|def| (def.hardness, def.rubble))
+        else {
+            return DamageOutcome::Immune;
+        };
+
+        let damage = self.update_tile_data(pos, |data| {
+            data.damage = data.damage.saturating_add(amount);
+            data.damage
+        });
+
+        if damage >= hardness {
+            self.set_tile(pos, rubble);
+            // Обломки того же материала сохраняют TileData — урон сбрасываем сами,
+            // иначе они развалятся от первого же удара
+            self.update_tile_data(pos, |data| data.damage = 0);
+            return DamageOutcome::Destroyed { rubble };
+        }
+
+        DamageOutcome::Damaged { damage, hardness }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MaterialDef, TileFlags};
+
+    const FLOOR: Tile = Tile { material: 1, flags: TileFlags::WALKABLE, variant: 0 };
+    const RUBBLE: Tile = Tile { material: 3, flags: TileFlags::WALKABLE, variant: 0 };
+
+    fn materials() -> MaterialRegistry {
+        let mut reg = MaterialRegistry::new();
+        reg.register(MaterialDef::simple(1, "Floor", TileFlags::WALKABLE));
+        reg.register(MaterialDef::simple(2, "Rock", TileFlags::SOLID | TileFlags::OPAQUE).destructible(10, RUBBLE));
+        reg
+    }
+
+    #[test]
+    fn test_dig_through_rock() {
+        let map = WorldMap::new();
+        let materials = materials();
+        let pos = WorldPos::new(1, 1, 0);
+        map.set_tile(pos, Tile { material: 2, flags: TileFlags::SOLID | TileFlags::OPAQUE, variant: 0 });
+
+        assert_eq!(map.damage_tile(pos, 4, &materials), DamageOutcome::Damaged { damage: 4, hardness: 10 });
+        assert_eq!(map.damage_tile(pos, 4, &materials), DamageOutcome::Damaged { damage: 8, hardness: 10 });
+        assert!(map.is_solid_fast(pos));
+
+        assert_eq!(map.damage_tile(pos, 4, &materials), DamageOutcome::Destroyed { rubble: RUBBLE });
+        assert_eq!(map.get_tile(pos), RUBBLE);
+        assert!(!map.is_solid_fast(pos));
+        assert_eq!(map.get_tile_data(pos), None); // Урон ушел вместе со скалой
+    }
+
+    #[test]
+    fn test_indestructible_tiles() {
+        let map = WorldMap::new();
+        let materials = materials();
+        let pos = WorldPos::new(0, 0, 0);
+        map.set_tile(pos, FLOOR);
+
+        assert_eq!(map.damage_tile(pos, 100, &materials), DamageOutcome::Immune);
+        assert_eq!(map.get_tile_data(pos), None);
+    }
+}
@@ -9,6 +9,41 @@ bitflags! {
         const OPAQUE   = 1 << 1; // Блокирует свет
         const LIQUID   = 1 << 2; // Вода/Лава
         const WALKABLE = 1 << 3; // Пол
+        const STAIRS_UP   = 1 << 4; // Лестница/лаз на уровень выше (z + 1)
+        const STAIRS_DOWN = 1 << 5; // Лестница/лаз на уровень ниже (z - 1)
+        const PORTAL      = 1 << 6; // Портал, цель в TileData::portal
+    }
+}
+
+// В архиве флаги лежат голым байтом: bitflags не умеет rkyv,
+// а u8 и так валиден при любом значении (лишние биты отбрасываются при чтении).
+#[cfg(feature = "rkyv")]
+#[allow(unsafe_code)]
+mod archive {
+    use super::TileFlags;
+    use rkyv::{Archive, Deserialize, Fallible, Serialize};
+
+    impl Archive for TileFlags {
+        type Archived = u8;
+        type Resolver = ();
+
+        #[inline]
+        unsafe fn resolve(&self, pos: usize, resolver: (), out: *mut u8) {
+            // Safety: out указывает на место под Archived (u8), как требует контракт resolve
+            unsafe { self.bits().resolve(pos, resolver, out) }
+        }
+    }
+
+    impl<S: Fallible + ?Sized> Serialize<S> for TileFlags {
+        fn serialize(&self, _: &mut S) -> Result<(), S::Error> {
+            Ok(())
+        }
+    }
+
+    impl<D: Fallible + ?Sized> Deserialize<TileFlags, D> for u8 {
+        fn deserialize(&self, _: &mut D) -> Result<TileFlags, D::Error> {
+            Ok(TileFlags::from_bits_truncate(*self))
+        }
     }
 }
 
@@ -18,6 +53,8 @@ pub type MaterialID = u16;
 /// Базовая единица карты.
 /// Стараемся уложить в 4 байта: 2 (Mat) + 1 (Flags) + 1 (Variant).
 #[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
 #[repr(C)] // Гарантирует layout как в C (поля по порядку)
 pub struct Tile {
     pub material: MaterialID,
@@ -30,6 +67,12 @@ impl Tile {
         self.material == 0
     }
 
+    /// Можно ли стоять на тайле: пол и не стена.
+    #[inline]
+    pub fn is_walkable(&self) -> bool {
+        self.flags.contains(TileFlags::WALKABLE) && !self.flags.contains(TileFlags::SOLID)
+    }
+
     #[inline(always)]
     pub const fn pack(self) -> u32 {
         ((self.variant as u32) << 24) | ((self.flags.bits() as u32) << 16) | (self.material as u32)
@@ -81,4 +124,25 @@ mod tests {
         let unpacked = Tile::unpack(packed);
         assert_eq!(original, unpacked);
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_tile_archive_roundtrip() {
+        let tile = Tile { material: 0xBEEF, flags: TileFlags::SOLID | TileFlags::PORTAL, variant: 9 };
+        let bytes = rkyv::to_bytes::<_, 64>(&tile).unwrap();
+        let archived = rkyv::check_archived_root::<Tile>(&bytes).unwrap();
+        // Флаги лежат голым байтом
+        assert_eq!(archived.flags, tile.flags.bits());
+        let restored: Tile = rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible).unwrap();
+        assert_eq!(restored, tile);
+
+        // Неизвестные биты отбрасываются при чтении, а не ломают тайл
+        let mut raw = bytes.to_vec();
+        let root = raw.len() - std::mem::size_of::<ArchivedTile>();
+        raw[root + 2] = 0xFF;
+        let archived = rkyv::check_archived_root::<Tile>(&raw).unwrap();
+        let restored: Tile = rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible).unwrap();
+        assert_eq!(restored.flags, TileFlags::all());
+        assert_eq!((restored.material, restored.variant), (tile.material, tile.variant));
+    }
+}
@@ -0,0 +1,132 @@
+use ahash::HashMap;
+use cd_core::{ObjectGuid, WorldPos};
+use serde::{Deserialize, Serialize};
+use crate::CHUNK_SHIFT;
+
+/// Параметры ловушки.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
+pub struct TrapParams {
+    pub kind: u16,
+    pub power: i32,
+    pub armed: bool,
+}
+
+/// Данные, привязанные к конкретному тайлу.
+/// `Tile` упакован в 4 байта и не может хранить ничего сверх `variant`,
+/// поэтому все "тяжелое" (текст, содержимое, параметры) живет здесь.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes))]
+pub struct TileData {
+    /// Текст таблички / надписи
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// ID ключа, которым отпирается замок (двери, сундуки)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_key: Option<u32>,
+    /// Содержимое контейнера
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contents: Vec<ObjectGuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trap: Option<TrapParams>,
+    /// Накопленный урон (копание, взрывы). Сравнивается с `MaterialDef::hardness`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub damage: u16,
+    /// Куда ведет портал (в том числе в другое измерение)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub portal: Option<WorldPos>,
+}
+
+fn is_zero(v: &u16) -> bool {
+    *v == 0
+}
+
+impl TileData {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Все данные тайлов одного чанка.
+/// Хранится отдельно от слоев тайлов (Region/SparseChunk), поэтому не зависит
+/// от того, где сейчас лежит сам тайл — в статике или в дельте.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChunkTileData {
+    // Ключ - упакованный индекс (ly << 4 | lx), как в SparseChunk
+    entries: HashMap<u8, TileData>,
+}
+
+impl ChunkTileData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, lx: usize, ly: usize) -> Option<&TileData> {
+        self.entries.get(&Self::key(lx, ly))
+    }
+
+    pub fn get_mut(&mut self, lx: usize, ly: usize) -> Option<&mut TileData> {
+        self.entries.get_mut(&Self::key(lx, ly))
+    }
+
+    /// Пустые данные не храним — это то же самое, что их отсутствие.
+    pub fn set(&mut self, lx: usize, ly: usize, data: TileData) {
+        if data.is_empty() {
+            self.remove(lx, ly);
+        } else {
+            self.entries.insert(Self::key(lx, ly), data);
+        }
+    }
+
+    pub fn remove(&mut self, lx: usize, ly: usize) -> Option<TileData> {
+        self.entries.remove(&Self::key(lx, ly))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Итерация по (lx, ly, data)
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &TileData)> {
+        self.entries.iter().map(|(&idx, data)| {
+            let idx = idx as usize;
+            (idx & ((1 << CHUNK_SHIFT) - 1), idx >> CHUNK_SHIFT, data)
+        })
+    }
+
+    #[inline]
+    fn key(lx: usize, ly: usize) -> u8 {
+        ((ly << CHUNK_SHIFT) | lx) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_data_is_not_stored() {
+        let mut chunk = ChunkTileData::new();
+        chunk.set(3, 4, TileData { text: Some("Hi".into()), ..Default::default() });
+        assert_eq!(chunk.len(), 1);
+
+        chunk.set(3, 4, TileData::default());
+        assert!(chunk.is_empty());
+    }
+
+    #[test]
+    fn test_iter_returns_local_coords() {
+        let mut chunk = ChunkTileData::new();
+        chunk.set(15, 2, TileData { lock_key: Some(7), ..Default::default() });
+
+        let (lx, ly, data) = chunk.iter().next().unwrap();
+        assert_eq!((lx, ly), (15, 2));
+        assert_eq!(data.lock_key, Some(7));
+    }
+}
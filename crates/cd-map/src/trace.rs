@@ -0,0 +1,113 @@
+use cd_core::shape::line;
+use cd_core::{ObjectGuid, WorldPos};
+use crate::{SpatialGrid, WorldMap};
+
+/// Результат трассировки луча (выстрел, заклинание).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceHit {
+    /// Последний тайл, до которого долетел луч (для попадания — тайл цели)
+    pub end: WorldPos,
+    /// Первый непрозрачный или твердый тайл на пути
+    pub blocked_at: Option<WorldPos>,
+    /// Первая сущность на пути
+    pub entity: Option<ObjectGuid>,
+}
+
+impl TraceHit {
+    /// Луч дошел до конца без препятствий.
+    pub fn is_clear(&self) -> bool {
+        self.blocked_at.is_none() && self.entity.is_none()
+    }
+}
+
+impl WorldMap {
+    /// Видна ли клетка `to` из `from`: между ними (без концов) нет непрозрачных тайлов.
+    /// Симметрична: `line_of_sight(a, b) == line_of_sight(b, a)`.
+    pub fn line_of_sight(&self, from: WorldPos, to: WorldPos) -> bool {
+        if from.z() != to.z() {
+            return false;
+        }
+        let line = line(from, to);
+        let inner = line.len().saturating_sub(2);
+        line.skip(1).take(inner).all(|p| !self.is_opaque_fast(p))
+    }
+
+    /// Провести луч из `from` в `to`, но не дальше `max_range` клеток (Чебышёв).
+    ///
+    /// Луч идет по той же линии, что и `line_of_sight`, поэтому цель, которую видно
+    /// (и которая в пределах дальности), всегда достижима — если путь не перекрыт
+    /// прозрачной, но твердой клеткой (решетка, стекло) или другой сущностью.
+    /// Стартовая клетка не проверяется (там стоит стрелок).
+    pub fn trace(&self, from: WorldPos, to: WorldPos, max_range: i32, grid: Option<&SpatialGrid>) -> TraceHit {
+        let mut hit = TraceHit { end: from, blocked_at: None, entity: None };
+        if from.z() != to.z() {
+            return hit;
+        }
+
+        for pos in line(from, to).skip(1).take(max_range.max(0) as usize) {
+            if self.is_solid_fast(pos) || self.is_opaque_fast(pos) {
+                hit.blocked_at = Some(pos);
+                return hit;
+            }
+            hit.end = pos;
+            if let Some(entity) = grid.and_then(|g| g.entities_at(pos).next()) {
+                hit.entity = Some(entity);
+                return hit;
+            }
+        }
+        hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tile, TileFlags};
+
+    const WALL: Tile = Tile { material: 1, flags: TileFlags::SOLID.union(TileFlags::OPAQUE), variant: 0 };
+    const BARS: Tile = Tile { material: 2, flags: TileFlags::SOLID, variant: 0 };
+
+    #[test]
+    fn test_line_of_sight_is_symmetric() {
+        let map = WorldMap::new();
+        for i in 0..40 {
+            map.set_tile(WorldPos::new((i * 7) % 13, (i * 5) % 11, 0), WALL);
+        }
+
+        for ay in 0..11 {
+            for ax in 0..13 {
+                let a = WorldPos::new(ax, ay, 0);
+                for (bx, by) in [(0, 0), (12, 10), (6, 3), (1, 9)] {
+                    let b = WorldPos::new(bx, by, 0);
+                    assert_eq!(map.line_of_sight(a, b), map.line_of_sight(b, a), "{:?} <-> {:?}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_trace_stops_at_walls_entities_and_range() {
+        let map = WorldMap::new();
+        let mut grid = SpatialGrid::new();
+        let from = WorldPos::new(0, 0, 0);
+        let target = ObjectGuid::player(0, 0, 7);
+        grid.insert(target, WorldPos::new(5, 0, 0));
+
+        let hit = map.trace(from, WorldPos::new(8, 0, 0), 20, Some(&grid));
+        assert_eq!(hit.entity, Some(target));
+        assert_eq!(hit.end, WorldPos::new(5, 0, 0));
+
+        // Дальности не хватает
+        let hit = map.trace(from, WorldPos::new(8, 0, 0), 3, Some(&grid));
+        assert!(hit.is_clear());
+        assert_eq!(hit.end, WorldPos::new(3, 0, 0));
+
+        // Решетка: видно, но не простреливается
+        map.set_tile(WorldPos::new(2, 0, 0), BARS);
+        assert!(map.line_of_sight(from, WorldPos::new(5, 0, 0)));
+        let hit = map.trace(from, WorldPos::new(8, 0, 0), 20, Some(&grid));
+        assert_eq!(hit.blocked_at, Some(WorldPos::new(2, 0, 0)));
+        assert_eq!(hit.end, WorldPos::new(1, 0, 0));
+        assert_eq!(hit.entity, None);
+    }
+}
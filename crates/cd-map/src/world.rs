@@ -1,9 +1,13 @@
+use std::ops::RangeInclusive;
 use std::sync::RwLock;
-use ahash::{HashMap, HashMapExt};
+use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use cd_core::WorldPos;
 use crate::region::{Region};
 use crate::shard::{Shard};
-use crate::{Chunk, Tile, REGION_MASK, SHARD_COUNT};
+use crate::lock;
+use crate::provider::ChunkProvider;
+use crate::tile_data::{ChunkTileData, TileData};
+use crate::{Chunk, Tile, REGION_MASK, REGION_SHIFT, REGION_SIZE, SHARD_COUNT};
 
 pub struct WorldMap {
     // Статический слой: Регионы
@@ -14,6 +18,11 @@ pub struct WorldMap {
     // Массив фиксированного размера
     shards: Box<[Shard; SHARD_COUNT]>,
 
+    // Источник регионов при первом обращении (диск / генератор)
+    provider: Option<Box<dyn ChunkProvider>>,
+    // Регионы, для которых провайдер уже вернул None или ошибку (чтобы не дергать его повторно)
+    missing: RwLock<HashSet<WorldPos>>,
+
     default_tile: Tile,
 }
 
@@ -28,10 +37,19 @@ impl WorldMap {
         Self {
             regions: RwLock::new(HashMap::new()),
             shards: Box::new(shards),
+            provider: None,
+            missing: RwLock::new(HashSet::new()),
             default_tile: Tile::default(),
         }
     }
 
+    /// Карта с ленивой подгрузкой: недостающие регионы запрашиваются у провайдера.
+    pub fn with_provider(provider: impl ChunkProvider + 'static) -> Self {
+        let mut map = Self::new();
+        map.provider = Some(Box::new(provider));
+        map
+    }
+
     // --- Public API ---
 
     pub fn get_tile(&self, pos: WorldPos) -> Tile {
@@ -65,8 +83,9 @@ impl WorldMap {
             return val;
         }
 
-        let regions = self.regions.read().unwrap();
         let region_key = chunk_key.region_key();
+        self.ensure_region(region_key);
+        let regions = self.regions.read().unwrap();
 
         if let Some(region) = regions.get(&region_key) {
             let (cx, cy, _) = chunk_key.xyz();
@@ -85,8 +104,15 @@ impl WorldMap {
         let (lx, ly) = pos.local_coords();
         let shard = &self.shards[chunk_key.shard_index()];
 
-        let regions_guard = self.regions.read().unwrap();
+        // Тайл заменен другим материалом (сундук сломали) — его данные больше не валидны.
+        // Смена состояния того же материала (дверь открылась) данные сохраняет.
+        if self.get_tile(pos).material != tile.material {
+            shard.remove_tile_data(chunk_key, lx, ly);
+        }
+
         let region_key = chunk_key.region_key();
+        self.ensure_region(region_key);
+        let regions_guard = self.regions.read().unwrap();
         // Получаем Snapshot базы для инициализации масок в дельте
         let base_chunk = regions_guard.get(&region_key).and_then(|r| {
             let (cx, cy, _) = chunk_key.xyz();
@@ -104,17 +130,139 @@ impl WorldMap {
         let rx = (cx & REGION_MASK) as usize;
         let ry = (cy & REGION_MASK) as usize;
 
+        self.ensure_region(region_key);
         let mut regions = self.regions.write().unwrap();
         let region = regions.entry(region_key).or_insert_with(Region::new);
         let dest_chunk = region.get_or_create_chunk(rx, ry);
         *dest_chunk = chunk;
     }
 
+    pub fn is_region_loaded(&self, region_key: WorldPos) -> bool {
+        self.regions.read().unwrap().contains_key(&region_key)
+    }
+
+    /// Выгрузить регион из памяти (например, перед сохранением на диск).
+    /// Данные тайлов его чанков уезжают вместе с ним.
+    /// При следующем обращении он снова будет запрошен у провайдера.
+    pub fn unload_region(&self, region_key: WorldPos) -> Option<Region> {
+        lock::write(&self.missing).remove(&region_key);
+        let region = lock::write(&self.regions).remove(&region_key);
+
+        let (rkx, rky, z) = region_key.xyz();
+        let mut tile_data = Vec::new();
+        for ry in 0..REGION_SIZE {
+            for rx in 0..REGION_SIZE {
+                let chunk_key = WorldPos::new((rkx << REGION_SHIFT) | rx as i32, (rky << REGION_SHIFT) | ry as i32, z);
+                if let Some(data) = self.shards[chunk_key.shard_index()].take_chunk_tile_data(chunk_key) {
+                    tile_data.push((rx, ry, data));
+                }
+            }
+        }
+
+        // Региона в памяти не было, но данные тайлов были — все равно отдаем их
+        if region.is_none() && tile_data.is_empty() {
+            return None;
+        }
+        let mut region = region.unwrap_or_default();
+        for (rx, ry, data) in tile_data {
+            region.set_chunk_tile_data(rx, ry, data);
+        }
+        Some(region)
+    }
+
+    /// Полностью стереть уровни `z_range` из памяти: статику, дельты и данные тайлов.
+    /// Нужно при удалении инстанса, чтобы следующий владелец диапазона получил чистое место.
+    pub fn clear_levels(&self, z_range: RangeInclusive<i32>) {
+        lock::write(&self.regions).retain(|key, _| !z_range.contains(&key.z()));
+        lock::write(&self.missing).retain(|key| !z_range.contains(&key.z()));
+        for shard in self.shards.iter() {
+            shard.retain_chunks(|key| !z_range.contains(&key.z()));
+        }
+    }
+
+    // --- Tile Data ---
+
+    pub fn get_tile_data(&self, pos: WorldPos) -> Option<TileData> {
+        let chunk_key = pos.chunk_key();
+        let (lx, ly) = pos.local_coords();
+        self.shards[chunk_key.shard_index()].get_tile_data(chunk_key, lx, ly)
+    }
+
+    pub fn set_tile_data(&self, pos: WorldPos, data: TileData) {
+        self.update_tile_data(pos, |d| *d = data);
+    }
+
+    /// Изменить данные тайла без копирования.
+    /// Если данных не было — `f` получит пустые; если после `f` они пустые — удаляются.
+    pub fn update_tile_data<R>(&self, pos: WorldPos, f: impl FnOnce(&mut TileData) -> R) -> R {
+        let chunk_key = pos.chunk_key();
+        let (lx, ly) = pos.local_coords();
+        self.shards[chunk_key.shard_index()].update_tile_data(chunk_key, lx, ly, f)
+    }
+
+    pub fn remove_tile_data(&self, pos: WorldPos) -> Option<TileData> {
+        let chunk_key = pos.chunk_key();
+        let (lx, ly) = pos.local_coords();
+        self.shards[chunk_key.shard_index()].remove_tile_data(chunk_key, lx, ly)
+    }
+
+    /// Снимок данных тайлов чанка (для сохранения региона).
+    pub fn chunk_tile_data(&self, chunk_key: WorldPos) -> Option<ChunkTileData> {
+        self.shards[chunk_key.shard_index()].chunk_tile_data(chunk_key)
+    }
+
+    /// Загрузка данных тайлов чанка (при загрузке региона). Заменяет текущие.
+    pub fn put_chunk_tile_data(&self, chunk_key: WorldPos, data: ChunkTileData) {
+        self.shards[chunk_key.shard_index()].put_chunk_tile_data(chunk_key, data);
+    }
+
     // --- Private Helpers ---
 
+    /// Подгружает регион через провайдер при первом обращении.
+    fn ensure_region(&self, region_key: WorldPos) {
+        let Some(provider) = &self.provider else { return };
+
+        if lock::read(&self.regions).contains_key(&region_key)
+            || lock::read(&self.missing).contains(&region_key)
+        {
+            return;
+        }
+
+        // Генерация может быть долгой — делаем ее без блокировок.
+        // Если два потока сгенерировали один регион одновременно, выигрывает первый.
+        match provider.provide_region(region_key) {
+            Ok(Some(mut region)) => {
+                let tile_data: Vec<_> = region.take_tile_data().collect();
+                {
+                    let mut regions = lock::write(&self.regions);
+                    if regions.contains_key(&region_key) {
+                        return; // Другой поток успел раньше — его данные уже разложены
+                    }
+                    regions.insert(region_key, region);
+                }
+
+                let (rkx, rky, z) = region_key.xyz();
+                for (rx, ry, data) in tile_data {
+                    let chunk_key = WorldPos::new((rkx << REGION_SHIFT) | rx as i32, (rky << REGION_SHIFT) | ry as i32, z);
+                    self.shards[chunk_key.shard_index()].merge_chunk_tile_data(chunk_key, data);
+                }
+            }
+            Ok(None) => {
+                lock::write(&self.missing).insert(region_key);
+            }
+            Err(e) => {
+                // Регион есть, но не читается. Генерировать на его месте нельзя —
+                // затрем данные при следующем сохранении. Пусть остается пустым до починки.
+                tracing::error!("Failed to load region {:?}: {}", region_key, e);
+                lock::write(&self.missing).insert(region_key);
+            }
+        }
+    }
+
     fn get_static_tile(&self, chunk_key: WorldPos, lx: usize, ly: usize) -> Option<Tile> {
-        let regions = self.regions.read().unwrap();
         let region_key = chunk_key.region_key();
+        self.ensure_region(region_key);
+        let regions = self.regions.read().unwrap();
 
         if let Some(region) = regions.get(&region_key) {
             let (cx, cy, _) = chunk_key.xyz();
@@ -131,6 +279,7 @@ impl WorldMap {
 #[cfg(test)]
 mod tests {
     use crate::TileFlags;
+    use cd_core::{Direction, GridLogic};
     use super::*;
 
     #[test]
@@ -163,6 +312,51 @@ mod tests {
         // (предполагаем, что t_dynamic.flags содержат LIQUID, но не SOLID)
     }
 
+    #[test]
+    fn test_tile_data_lifecycle() {
+        let world = WorldMap::new();
+        let pos = WorldPos::new(-3, 7, 0);
+        let door = Tile { material: 5, flags: TileFlags::SOLID, variant: 0 };
+        world.set_tile(pos, door);
+        world.set_tile_data(pos, TileData { lock_key: Some(42), ..Default::default() });
+
+        // Смена состояния того же материала сохраняет данные
+        world.set_tile(pos, Tile { variant: 1, flags: TileFlags::WALKABLE, ..door });
+        assert_eq!(world.get_tile_data(pos).and_then(|d| d.lock_key), Some(42));
+
+        // Экспорт/импорт чанка (сохранение региона)
+        let saved = world.chunk_tile_data(pos.chunk_key()).unwrap();
+        let restored = WorldMap::new();
+        restored.put_chunk_tile_data(pos.chunk_key(), saved);
+        assert_eq!(restored.get_tile_data(pos).and_then(|d| d.lock_key), Some(42));
+
+        // Замена тайла другим материалом чистит данные
+        world.set_tile(pos, Tile { material: 1, flags: TileFlags::WALKABLE, variant: 0 });
+        assert_eq!(world.get_tile_data(pos), None);
+        assert_eq!(world.chunk_tile_data(pos.chunk_key()), None);
+    }
+
+    #[test]
+    fn test_clear_levels_keeps_other_floors() {
+        let world = WorldMap::new();
+        let wall = Tile { material: 3, flags: TileFlags::SOLID, variant: 0 };
+        let (inside, outside) = (WorldPos::new(1, 1, -500), WorldPos::new(1, 1, 0));
+
+        let mut chunk = Chunk::new();
+        chunk.set_tile(1, 1, wall);
+        world.put_chunk(inside.chunk_key(), chunk.clone());
+        world.put_chunk(outside.chunk_key(), chunk);
+        world.set_tile(inside.shift(Direction::East), wall);
+        world.set_tile_data(inside, TileData { text: Some("x".into()), ..Default::default() });
+
+        world.clear_levels(-510..=-500);
+
+        assert_eq!(world.get_tile(inside), Tile::default());
+        assert_eq!(world.get_tile(inside.shift(Direction::East)), Tile::default());
+        assert_eq!(world.get_tile_data(inside), None);
+        assert_eq!(world.get_tile(outside), wall);
+    }
+
     #[test]
     fn test_threading_smoke_test() {
         // Простейший тест на дедлоки (хотя для полноценной проверки нужны потоки)
@@ -0,0 +1,203 @@
+use cd_core::WorldPos;
+use crate::chunk::ChunkBuilder;
+use crate::provider::ChunkGenerator;
+use crate::worldgen::noise::{hash2, Fbm};
+use crate::worldgen::overworld::OverworldGenerator;
+use crate::{Chunk, MaterialID, Tile, TileFlags, CHUNK_SHIFT, CHUNK_SIZE};
+
+#[derive(Debug, Clone, Copy)]
+pub struct DungeonMaterials {
+    pub floor: MaterialID,
+    pub wall: MaterialID,
+    pub stairs: MaterialID,
+}
+
+impl Default for DungeonMaterials {
+    fn default() -> Self {
+        Self { floor: 200, wall: 201, stairs: 202 }
+    }
+}
+
+/// Генератор подземных этажей под overworld.
+///
+/// Этажи лежат на z = surface - 1 ..= surface - depth. Лестницы парные:
+/// спуск на этаже z (или вход в подземелье на поверхности) всегда стоит над
+/// подъемом на этаже z - 1 в тех же (x, y). Оба этажа вычисляют позицию пары
+/// одной и той же функцией `down_stairs`, поэтому генерируются независимо.
+#[derive(Debug, Clone)]
+pub struct DungeonGenerator {
+    seed: u64,
+    pub overworld: OverworldGenerator,
+    /// Количество этажей под поверхностью
+    pub depth: i32,
+    /// Спуск на следующий этаж — примерно в одном чанке из N
+    pub stairs_rarity: u64,
+    /// Доля стен в пещерах (порог шума)
+    pub wall_threshold: f32,
+    pub materials: DungeonMaterials,
+}
+
+const SALT_CAVES: i32 = 10;
+const SALT_STAIRS: i32 = 11;
+
+impl DungeonGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            overworld: OverworldGenerator::new(seed),
+            depth: 8,
+            stairs_rarity: 6,
+            wall_threshold: 0.52,
+            materials: DungeonMaterials::default(),
+        }
+    }
+
+    pub fn surface_z(&self) -> i32 {
+        self.overworld.config.z
+    }
+
+    /// Есть ли подземный этаж на этом уровне.
+    pub fn has_floor(&self, z: i32) -> bool {
+        let surface = self.surface_z();
+        z < surface && z >= surface - self.depth
+    }
+
+    /// Спуск вниз в чанке (если есть).
+    /// На поверхности это вход в подземелье, на последнем этаже спусков нет.
+    pub fn down_stairs(&self, chunk_key: WorldPos) -> Option<WorldPos> {
+        let (cx, cy, z) = chunk_key.xyz();
+        if z == self.surface_z() {
+            return self.overworld.dungeon_entrance(chunk_key);
+        }
+        if !self.has_floor(z) || !self.has_floor(z - 1) {
+            return None;
+        }
+
+        let h = hash2(self.seed, cx.wrapping_mul(31).wrapping_add(SALT_STAIRS), cy ^ z.wrapping_mul(0x9E37));
+        if !h.is_multiple_of(self.stairs_rarity.max(1)) {
+            return None;
+        }
+
+        // Не у самого края чанка, чтобы вокруг поместилась площадка 3x3
+        let inner = CHUNK_SIZE as u64 - 2;
+        let lx = 1 + ((h >> 8) % inner) as i32;
+        let ly = 1 + ((h >> 24) % inner) as i32;
+        Some(WorldPos::new((cx << CHUNK_SHIFT) + lx, (cy << CHUNK_SHIFT) + ly, z))
+    }
+
+    /// Подъем в чанке (если есть) — ровно под спуском этажа выше.
+    pub fn up_stairs(&self, chunk_key: WorldPos) -> Option<WorldPos> {
+        let (cx, cy, z) = chunk_key.xyz();
+        if !self.has_floor(z) {
+            return None;
+        }
+        let above = self.down_stairs(WorldPos::new(cx, cy, z + 1))?;
+        Some(WorldPos::new(above.x(), above.y(), z))
+    }
+
+    fn generate_floor(&self, chunk_key: WorldPos) -> Chunk {
+        let (cx, cy, z) = chunk_key.xyz();
+        let caves = Fbm::new(hash2(self.seed, SALT_CAVES, z), 3, 1.0 / 12.0);
+        let m = &self.materials;
+
+        let floor = Tile { material: m.floor, flags: TileFlags::WALKABLE, variant: 0 };
+        let wall = Tile { material: m.wall, flags: TileFlags::SOLID | TileFlags::OPAQUE, variant: 0 };
+
+        let mut tiles = [[floor; CHUNK_SIZE as usize]; CHUNK_SIZE as usize];
+        let (bx, by) = (cx << CHUNK_SHIFT, cy << CHUNK_SHIFT);
+        for (ly, row) in tiles.iter_mut().enumerate() {
+            for (lx, tile) in row.iter_mut().enumerate() {
+                if caves.sample((bx + lx as i32) as f32, (by + ly as i32) as f32) > self.wall_threshold {
+                    *tile = wall;
+                }
+            }
+        }
+
+        // Лестницы: расчищаем площадку вокруг и ставим тайл лестницы
+        let stairs = [
+            (self.up_stairs(chunk_key), TileFlags::STAIRS_UP),
+            (self.down_stairs(chunk_key), TileFlags::STAIRS_DOWN),
+        ];
+        for (pos, _) in &stairs {
+            let Some(pos) = pos else { continue };
+            let (lx, ly) = pos.local_coords();
+            for row in tiles.iter_mut().skip(ly.saturating_sub(1)).take(3) {
+                for tile in row.iter_mut().skip(lx.saturating_sub(1)).take(3) {
+                    *tile = floor;
+                }
+            }
+        }
+        for (pos, flag) in stairs {
+            let Some(pos) = pos else { continue };
+            let (lx, ly) = pos.local_coords();
+            let tile = &mut tiles[ly][lx];
+            // Подъем и спуск могут совпасть — тогда тайл ведет в обе стороны
+            let extra = if tile.material == m.stairs { tile.flags } else { TileFlags::WALKABLE };
+            *tile = Tile { material: m.stairs, flags: extra | flag, variant: 0 };
+        }
+
+        let mut builder = ChunkBuilder::new();
+        for (ly, row) in tiles.iter().enumerate() {
+            for (lx, tile) in row.iter().enumerate() {
+                builder.set_tile(lx, ly, *tile);
+            }
+        }
+        builder.build()
+    }
+}
+
+impl ChunkGenerator for DungeonGenerator {
+    fn generate_chunk(&self, chunk_key: WorldPos) -> Option<Chunk> {
+        let z = chunk_key.z();
+        if z == self.surface_z() {
+            return self.overworld.generate_chunk(chunk_key);
+        }
+        self.has_floor(z).then(|| self.generate_floor(chunk_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeneratorProvider, WorldMap};
+    use cd_core::Direction;
+
+    #[test]
+    fn test_stairs_pair_between_floors() {
+        let generator = DungeonGenerator::new(11);
+        let map = WorldMap::with_provider(GeneratorProvider::new(generator.clone()));
+        let mut pairs = 0;
+
+        for z in generator.surface_z() - generator.depth + 1..=generator.surface_z() {
+            for cy in -6..6 {
+                for cx in -6..6 {
+                    let Some(down) = generator.down_stairs(WorldPos::new(cx, cy, z)) else { continue };
+                    pairs += 1;
+
+                    let below = map.stair_destination(down, Direction::Down);
+                    assert_eq!(below, Some(WorldPos::new(down.x(), down.y(), z - 1)), "no pair for {:?}", down);
+                    assert_eq!(map.stair_destination(WorldPos::new(down.x(), down.y(), z - 1), Direction::Up), Some(down));
+                }
+            }
+        }
+        assert!(pairs > 0);
+    }
+
+    #[test]
+    fn test_floors_exist_only_within_depth() {
+        let generator = DungeonGenerator::new(3);
+        let key = |z| WorldPos::new(0, 0, z);
+
+        assert!(generator.generate_chunk(key(-1)).is_some());
+        assert!(generator.generate_chunk(key(-generator.depth)).is_some());
+        assert!(generator.generate_chunk(key(-generator.depth - 1)).is_none());
+        assert!(generator.generate_chunk(key(1)).is_none());
+
+        // С последнего этажа вниз не ведет ничего
+        for cy in -10..10 {
+            for cx in -10..10 {
+                assert!(generator.down_stairs(WorldPos::new(cx, cy, -generator.depth)).is_none());
+            }
+        }
+    }
+}
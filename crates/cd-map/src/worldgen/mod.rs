@@ -0,0 +1,7 @@
+// Генераторы мира. Все детерминированы по seed и подключаются к `WorldMap` через `ChunkProvider`.
+pub mod noise;
+pub mod overworld;
+pub mod dungeon;
+
+pub use dungeon::{DungeonGenerator, DungeonMaterials};
+pub use overworld::{Biome, OverworldConfig, OverworldGenerator, OverworldMaterials};
@@ -0,0 +1,316 @@
+use cd_core::WorldPos;
+use crate::chunk::ChunkBuilder;
+use crate::worldgen::noise::{hash2, Fbm};
+use crate::provider::ChunkGenerator;
+use crate::{Chunk, MaterialID, Tile, TileFlags, CHUNK_SHIFT, CHUNK_SIZE};
+
+/// Биом поверхности.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Ocean,
+    Beach,
+    Plains,
+    Forest,
+    Desert,
+    Swamp,
+    Mountains,
+    Snow,
+}
+
+/// Какие материалы генератор ставит для каждого биома.
+#[derive(Debug, Clone, Copy)]
+pub struct OverworldMaterials {
+    pub ocean: MaterialID,
+    pub river: MaterialID,
+    pub sand: MaterialID,
+    pub grass: MaterialID,
+    pub forest: MaterialID,
+    pub desert: MaterialID,
+    pub swamp: MaterialID,
+    pub rock: MaterialID,
+    pub snow: MaterialID,
+    pub dungeon_entrance: MaterialID,
+}
+
+impl Default for OverworldMaterials {
+    fn default() -> Self {
+        Self {
+            ocean: 100,
+            river: 101,
+            sand: 102,
+            grass: 103,
+            forest: 104,
+            desert: 105,
+            swamp: 106,
+            rock: 107,
+            snow: 108,
+            dungeon_entrance: 109,
+        }
+    }
+}
+
+/// Пороги генерации. Высота и влажность нормализованы в [0, 1).
+#[derive(Debug, Clone, Copy)]
+pub struct OverworldConfig {
+    /// Уровень поверхности (Z), на котором лежит overworld
+    pub z: i32,
+    pub sea_level: f32,
+    pub beach_level: f32,
+    pub mountain_level: f32,
+    pub snow_level: f32,
+    /// Полуширина русла в единицах шума рек
+    pub river_width: f32,
+    /// Вход в подземелье — примерно в одном чанке из N
+    pub entrance_rarity: u64,
+}
+
+impl Default for OverworldConfig {
+    fn default() -> Self {
+        Self {
+            z: 0,
+            sea_level: 0.38,
+            beach_level: 0.41,
+            mountain_level: 0.68,
+            snow_level: 0.78,
+            river_width: 0.012,
+            entrance_rarity: 24,
+        }
+    }
+}
+
+/// Генератор поверхности на многослойном шуме: высота + влажность -> биом -> тайл.
+/// Результат зависит только от (seed, координаты), поэтому любой регион после выгрузки
+/// генерируется заново байт-в-байт.
+#[derive(Debug, Clone)]
+pub struct OverworldGenerator {
+    seed: u64,
+    elevation: Fbm,
+    moisture: Fbm,
+    rivers: Fbm,
+    pub config: OverworldConfig,
+    pub materials: OverworldMaterials,
+}
+
+// Соли для независимых потоков шума из одного seed
+const SALT_ELEVATION: i32 = 1;
+const SALT_MOISTURE: i32 = 2;
+const SALT_RIVERS: i32 = 3;
+const SALT_ENTRANCE: i32 = 4;
+
+impl OverworldGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            elevation: Fbm::new(hash2(seed, SALT_ELEVATION, 0), 5, 1.0 / 256.0),
+            moisture: Fbm::new(hash2(seed, SALT_MOISTURE, 0), 4, 1.0 / 192.0),
+            rivers: Fbm::new(hash2(seed, SALT_RIVERS, 0), 3, 1.0 / 320.0),
+            config: OverworldConfig::default(),
+            materials: OverworldMaterials::default(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn elevation(&self, x: i32, y: i32) -> f32 {
+        self.elevation.sample(x as f32, y as f32)
+    }
+
+    pub fn moisture(&self, x: i32, y: i32) -> f32 {
+        self.moisture.sample(x as f32, y as f32)
+    }
+
+    /// Река — узкая полоса вдоль "изолинии" 0.5 шума рек, только на суше и ниже гор.
+    pub fn is_river(&self, x: i32, y: i32) -> bool {
+        let e = self.elevation(x, y);
+        if e < self.config.beach_level || e >= self.config.mountain_level {
+            return false;
+        }
+        (self.rivers.sample(x as f32, y as f32) - 0.5).abs() < self.config.river_width
+    }
+
+    pub fn biome(&self, x: i32, y: i32) -> Biome {
+        let c = &self.config;
+        let e = self.elevation(x, y);
+        if e < c.sea_level {
+            return Biome::Ocean;
+        }
+        if e < c.beach_level {
+            return Biome::Beach;
+        }
+        if e >= c.snow_level {
+            return Biome::Snow;
+        }
+        if e >= c.mountain_level {
+            return Biome::Mountains;
+        }
+
+        match self.moisture(x, y) {
+            m if m < 0.35 => Biome::Desert,
+            m if m < 0.55 => Biome::Plains,
+            m if m < 0.7 => Biome::Forest,
+            _ => Biome::Swamp,
+        }
+    }
+
+    pub fn tile_at(&self, x: i32, y: i32) -> Tile {
+        let m = &self.materials;
+        if self.is_river(x, y) {
+            return Tile { material: m.river, flags: TileFlags::LIQUID, variant: 0 };
+        }
+
+        let (material, flags) = match self.biome(x, y) {
+            Biome::Ocean => (m.ocean, TileFlags::LIQUID),
+            Biome::Beach => (m.sand, TileFlags::WALKABLE),
+            Biome::Plains => (m.grass, TileFlags::WALKABLE),
+            Biome::Forest => (m.forest, TileFlags::WALKABLE),
+            Biome::Desert => (m.desert, TileFlags::WALKABLE),
+            Biome::Swamp => (m.swamp, TileFlags::WALKABLE),
+            Biome::Mountains => (m.rock, TileFlags::SOLID | TileFlags::OPAQUE),
+            Biome::Snow => (m.snow, TileFlags::WALKABLE),
+        };
+        Tile { material, flags, variant: 0 }
+    }
+
+    /// Вход в подземелье внутри чанка, если он там есть.
+    /// Ставится только на сухую проходимую землю (не река, не берег, не горы).
+    pub fn dungeon_entrance(&self, chunk_key: WorldPos) -> Option<WorldPos> {
+        let (cx, cy, _) = chunk_key.xyz();
+        let h = hash2(self.seed, cx.wrapping_mul(31).wrapping_add(SALT_ENTRANCE), cy);
+        if !h.is_multiple_of(self.config.entrance_rarity.max(1)) {
+            return None;
+        }
+
+        // Несколько детерминированных попыток найти подходящее место
+        (0..8).find_map(|attempt| {
+            let r = hash2(h, attempt, 0);
+            let lx = (r & (CHUNK_SIZE as u64 - 1)) as i32;
+            let ly = ((r >> 8) & (CHUNK_SIZE as u64 - 1)) as i32;
+            let (x, y) = ((cx << CHUNK_SHIFT) + lx, (cy << CHUNK_SHIFT) + ly);
+
+            let suitable = matches!(self.biome(x, y), Biome::Plains | Biome::Forest | Biome::Desert)
+                && !self.is_river(x, y);
+            suitable.then(|| WorldPos::new(x, y, self.config.z))
+        })
+    }
+}
+
+impl ChunkGenerator for OverworldGenerator {
+    fn generate_chunk(&self, chunk_key: WorldPos) -> Option<Chunk> {
+        let (cx, cy, cz) = chunk_key.xyz();
+        if cz != self.config.z {
+            return None; // Подземные уровни генерирует не overworld
+        }
+
+        let (bx, by) = (cx << CHUNK_SHIFT, cy << CHUNK_SHIFT);
+        let mut builder = ChunkBuilder::new();
+        for ly in 0..CHUNK_SIZE {
+            for lx in 0..CHUNK_SIZE {
+                builder.set_tile(lx as usize, ly as usize, self.tile_at(bx + lx, by + ly));
+            }
+        }
+
+        if let Some(entrance) = self.dungeon_entrance(chunk_key) {
+            let (lx, ly) = entrance.local_coords();
+            let flags = TileFlags::WALKABLE | TileFlags::STAIRS_DOWN;
+            let tile = Tile { material: self.materials.dungeon_entrance, flags, variant: 0 };
+            builder.set_tile(lx, ly, tile);
+        }
+
+        Some(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeneratorProvider, WorldMap};
+
+    fn same_chunk(a: &Chunk, b: &Chunk) -> bool {
+        (0..16).all(|y| (0..16).all(|x| a.get_tile(x, y) == b.get_tile(x, y)))
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let a = OverworldGenerator::new(42);
+        let b = OverworldGenerator::new(42);
+        let c = OverworldGenerator::new(43);
+
+        let mut differs = false;
+        for cy in -3..3 {
+            for cx in -3..3 {
+                let key = WorldPos::new(cx * 7, cy * 5, 0);
+                let (ca, cb, cc) = (
+                    a.generate_chunk(key).unwrap(),
+                    b.generate_chunk(key).unwrap(),
+                    c.generate_chunk(key).unwrap(),
+                );
+                assert!(same_chunk(&ca, &cb));
+                differs |= !same_chunk(&ca, &cc);
+            }
+        }
+        assert!(differs);
+        assert!(a.generate_chunk(WorldPos::new(0, 0, -1)).is_none());
+    }
+
+    #[test]
+    fn test_region_regenerates_after_eviction() {
+        let map = WorldMap::with_provider(GeneratorProvider::new(OverworldGenerator::new(7)));
+        let probe: Vec<WorldPos> = (0..64).map(|i| WorldPos::new(i * 13 - 200, i * 7 - 100, 0)).collect();
+
+        let before: Vec<Tile> = probe.iter().map(|&p| map.get_tile(p)).collect();
+        for p in &probe {
+            map.unload_region(p.chunk_key().region_key());
+        }
+        let after: Vec<Tile> = probe.iter().map(|&p| map.get_tile(p)).collect();
+
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_tiles_match_biomes() {
+        let generator = OverworldGenerator::new(2024);
+        let mut seen_water = false;
+        let mut seen_land = false;
+
+        for y in (-2000..2000).step_by(37) {
+            for x in (-2000..2000).step_by(41) {
+                let tile = generator.tile_at(x, y);
+                match generator.biome(x, y) {
+                    Biome::Ocean => assert!(tile.flags.contains(TileFlags::LIQUID)),
+                    Biome::Mountains if !generator.is_river(x, y) => {
+                        assert!(tile.flags.contains(TileFlags::SOLID))
+                    }
+                    _ => {}
+                }
+                seen_water |= tile.flags.contains(TileFlags::LIQUID);
+                seen_land |= tile.is_walkable();
+            }
+        }
+        assert!(seen_water && seen_land);
+    }
+
+    #[test]
+    fn test_entrances_are_on_dry_land() {
+        let generator = OverworldGenerator::new(5);
+        let mut found = 0;
+
+        for cy in -20..20 {
+            for cx in -20..20 {
+                let key = WorldPos::new(cx, cy, 0);
+                if let Some(pos) = generator.dungeon_entrance(key) {
+                    found += 1;
+                    assert_eq!(pos.chunk_key(), key);
+                    let chunk = generator.generate_chunk(key).unwrap();
+                    let (lx, ly) = pos.local_coords();
+                    let tile = chunk.get_tile(lx, ly);
+                    assert_eq!(tile.material, generator.materials.dungeon_entrance);
+                    assert!(tile.is_walkable());
+                    assert!(tile.flags.contains(TileFlags::STAIRS_DOWN));
+                }
+            }
+        }
+        assert!(found > 0);
+    }
+}
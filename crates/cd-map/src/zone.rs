@@ -0,0 +1,242 @@
+use ahash::{HashMap, HashMapExt};
+use cd_core::WorldPos;
+use crate::bitmask::BitMask256;
+use crate::{CHUNK_SHIFT, CHUNK_SIZE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ZoneId(pub u32);
+
+/// Тип зоны. Определяет игровые правила внутри нее.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZoneKind {
+    Town,
+    BossArena,
+    NoPvp,
+    Spawn,
+    /// Для скриптов и контента, который не укладывается в стандартные типы
+    Custom(u16),
+}
+
+/// Форма зоны. Z берется из координат (зона живет на одном уровне).
+#[derive(Debug, Clone)]
+pub enum ZoneShape {
+    /// Прямоугольник [min, max] включительно, на уровне min.z()
+    Rect { min: WorldPos, max: WorldPos },
+    /// Произвольный набор тайлов
+    Mask(Vec<WorldPos>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Zone {
+    pub id: ZoneId,
+    pub name: String,
+    pub kind: ZoneKind,
+    pub shape: ZoneShape,
+}
+
+/// Результат перехода сущности между двумя тайлами.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZoneTransition {
+    pub entered: Vec<ZoneId>,
+    pub left: Vec<ZoneId>,
+}
+
+impl ZoneTransition {
+    pub fn is_empty(&self) -> bool {
+        self.entered.is_empty() && self.left.is_empty()
+    }
+}
+
+/// Покрытие одного чанка одной зоной.
+#[derive(Debug, Clone)]
+struct ChunkCoverage {
+    zone: ZoneId,
+    mask: BitMask256,
+}
+
+/// Реестр именованных зон поверх карты.
+/// Индексирован по чанкам: поиск "в каких зонах точка" — один lookup в HashMap
+/// и проверка бита в маске для каждой зоны этого чанка (обычно 0-2).
+#[derive(Debug, Default)]
+pub struct ZoneRegistry {
+    zones: HashMap<ZoneId, Zone>,
+    // chunk_key -> зоны, пересекающие чанк
+    index: HashMap<WorldPos, Vec<ChunkCoverage>>,
+    next_id: u32,
+}
+
+impl ZoneRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Регистрирует зону и возвращает ее ID.
+    pub fn add(&mut self, name: impl Into<String>, kind: ZoneKind, shape: ZoneShape) -> ZoneId {
+        self.next_id += 1;
+        let id = ZoneId(self.next_id);
+
+        for (chunk_key, mask) in Self::rasterize(&shape) {
+            self.index.entry(chunk_key).or_default().push(ChunkCoverage { zone: id, mask });
+        }
+
+        self.zones.insert(id, Zone { id, name: name.into(), kind, shape });
+        id
+    }
+
+    pub fn remove(&mut self, id: ZoneId) -> Option<Zone> {
+        let zone = self.zones.remove(&id)?;
+        self.index.retain(|_, list| {
+            list.retain(|c| c.zone != id);
+            !list.is_empty()
+        });
+        Some(zone)
+    }
+
+    pub fn get(&self, id: ZoneId) -> Option<&Zone> {
+        self.zones.get(&id)
+    }
+
+    /// Зоны, содержащие точку.
+    pub fn zones_at(&self, pos: WorldPos) -> impl Iterator<Item = ZoneId> + '_ {
+        let (lx, ly) = pos.local_coords();
+        let idx = (ly << CHUNK_SHIFT) | lx;
+
+        self.index
+            .get(&pos.chunk_key())
+            .into_iter()
+            .flatten()
+            .filter(move |c| c.mask.get(idx))
+            .map(|c| c.zone)
+    }
+
+    pub fn contains(&self, id: ZoneId, pos: WorldPos) -> bool {
+        self.zones_at(pos).any(|z| z == id)
+    }
+
+    /// Какие зоны сущность покинула и в какие вошла при перемещении `from -> to`.
+    pub fn transition(&self, from: WorldPos, to: WorldPos) -> ZoneTransition {
+        // Быстрый путь: тот же чанк и нет зон — ничего не считаем
+        if from.chunk_key() == to.chunk_key() && !self.index.contains_key(&to.chunk_key()) {
+            return ZoneTransition::default();
+        }
+
+        let before: Vec<ZoneId> = self.zones_at(from).collect();
+        let after: Vec<ZoneId> = self.zones_at(to).collect();
+
+        ZoneTransition {
+            entered: after.iter().copied().filter(|z| !before.contains(z)).collect(),
+            left: before.iter().copied().filter(|z| !after.contains(z)).collect(),
+        }
+    }
+
+    // --- Private Helpers ---
+
+    /// Переводит форму в набор масок по чанкам.
+    fn rasterize(shape: &ZoneShape) -> HashMap<WorldPos, BitMask256> {
+        let mut masks: HashMap<WorldPos, BitMask256> = HashMap::new();
+
+        match shape {
+            ZoneShape::Rect { min, max } => {
+                let (x0, x1) = (min.x().min(max.x()), min.x().max(max.x()));
+                let (y0, y1) = (min.y().min(max.y()), min.y().max(max.y()));
+                let z = min.z();
+
+                // Идем по чанкам, а не по тайлам: большие зоны (города) не тормозят
+                let mut cy = y0 >> CHUNK_SHIFT;
+                while cy <= y1 >> CHUNK_SHIFT {
+                    let mut cx = x0 >> CHUNK_SHIFT;
+                    while cx <= x1 >> CHUNK_SHIFT {
+                        let (bx, by) = (cx << CHUNK_SHIFT, cy << CHUNK_SHIFT);
+                        let mut mask = BitMask256::default();
+
+                        for ly in 0..CHUNK_SIZE {
+                            for lx in 0..CHUNK_SIZE {
+                                let (x, y) = (bx + lx, by + ly);
+                                if x >= x0 && x <= x1 && y >= y0 && y <= y1 {
+                                    mask.set(((ly << CHUNK_SHIFT) | lx) as usize, true);
+                                }
+                            }
+                        }
+
+                        masks.insert(WorldPos::new(cx, cy, z), mask);
+                        cx += 1;
+                    }
+                    cy += 1;
+                }
+            }
+            ZoneShape::Mask(tiles) => {
+                for pos in tiles {
+                    let (lx, ly) = pos.local_coords();
+                    masks.entry(pos.chunk_key()).or_default().set((ly << CHUNK_SHIFT) | lx, true);
+                }
+            }
+        }
+
+        masks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect_zone_lookup_across_chunks() {
+        let mut zones = ZoneRegistry::new();
+        let town = zones.add(
+            "Town",
+            ZoneKind::Town,
+            ZoneShape::Rect { min: WorldPos::new(-5, -5, 0), max: WorldPos::new(20, 3, 0) },
+        );
+
+        assert!(zones.contains(town, WorldPos::new(-5, -5, 0)));
+        assert!(zones.contains(town, WorldPos::new(20, 3, 0)));
+        assert!(zones.contains(town, WorldPos::new(0, 0, 0)));
+        assert!(!zones.contains(town, WorldPos::new(21, 0, 0)));
+        assert!(!zones.contains(town, WorldPos::new(0, 4, 0)));
+        assert!(!zones.contains(town, WorldPos::new(0, 0, 1))); // Другой уровень
+    }
+
+    #[test]
+    fn test_mask_zone_and_overlap() {
+        let mut zones = ZoneRegistry::new();
+        let arena = zones.add(
+            "Arena",
+            ZoneKind::BossArena,
+            ZoneShape::Rect { min: WorldPos::new(0, 0, 0), max: WorldPos::new(9, 9, 0) },
+        );
+        let trap = zones.add(
+            "Pit",
+            ZoneKind::Custom(7),
+            ZoneShape::Mask(vec![WorldPos::new(5, 5, 0), WorldPos::new(40, 40, 0)]),
+        );
+
+        let mut at_pit: Vec<_> = zones.zones_at(WorldPos::new(5, 5, 0)).collect();
+        at_pit.sort();
+        assert_eq!(at_pit, vec![arena, trap]);
+        assert_eq!(zones.zones_at(WorldPos::new(40, 40, 0)).collect::<Vec<_>>(), vec![trap]);
+        assert_eq!(zones.zones_at(WorldPos::new(5, 6, 0)).collect::<Vec<_>>(), vec![arena]);
+
+        zones.remove(trap);
+        assert_eq!(zones.zones_at(WorldPos::new(40, 40, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_transition_enter_leave() {
+        let mut zones = ZoneRegistry::new();
+        let safe = zones.add(
+            "Safe",
+            ZoneKind::NoPvp,
+            ZoneShape::Rect { min: WorldPos::new(0, 0, 0), max: WorldPos::new(3, 3, 0) },
+        );
+
+        let t = zones.transition(WorldPos::new(4, 0, 0), WorldPos::new(3, 0, 0));
+        assert_eq!(t.entered, vec![safe]);
+        assert!(t.left.is_empty());
+
+        let t = zones.transition(WorldPos::new(3, 0, 0), WorldPos::new(4, 0, 0));
+        assert_eq!(t.left, vec![safe]);
+
+        assert!(zones.transition(WorldPos::new(1, 1, 0), WorldPos::new(2, 2, 0)).is_empty());
+    }
+}
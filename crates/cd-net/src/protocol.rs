@@ -5,7 +5,8 @@ use serde::{Deserialize, Serialize};
 #[serde(tag = "op", content = "d")] // { "op": "LOGIN", "d": { ... } }
 pub enum ClientPacket {
     Login { token: String },
-    Move { x: i32, y: i32 },
+    /// z необязателен (старые клиенты): по умолчанию поверхность
+    Move { x: i32, y: i32, #[serde(default)] z: i32 },
     // Cast { spell_id: u32, target_guid: String }
 }
 
@@ -6,11 +6,11 @@ use axum::{
     routing::get,
     Router,
 };
-use cd_core::{ObjectGuid, WorldPos};
+use cd_core::{GuidAllocator, ObjectGuid, ObjectKind, WorldPos};
 use cd_engine::InputCmd;
 use futures::{sink::SinkExt, stream::StreamExt};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 use tokio::sync::{broadcast, mpsc};
 use tracing::{error, info, warn};
 
@@ -20,16 +20,20 @@ struct AppState {
     cmd_tx: mpsc::Sender<InputCmd>,
     /// Канал для получения обновлений мира (подписка)
     snapshot_tx: broadcast::Sender<ServerPacket>,
+    /// Выдача GUID игрокам (общая с движком)
+    guids: Arc<Mutex<GuidAllocator>>,
 }
 
 pub async fn run_server(
     port: u16,
     cmd_tx: mpsc::Sender<InputCmd>,
     snapshot_tx: broadcast::Sender<ServerPacket>,
+    guids: Arc<Mutex<GuidAllocator>>,
 ) {
     let state = Arc::new(AppState {
         cmd_tx,
         snapshot_tx,
+        guids,
     });
 
     let app = Router::new()
@@ -84,27 +88,45 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
 
             // 2. Обработка (Auth или Command)
             match packet {
-                ClientPacket::Login { token } => {
-                    // TODO: Реальная авторизация
-                    // Пока генерируем фейковый GUID на основе длины токена для теста
-                    let mock_id = token.len() as u32;
-                    let guid = ObjectGuid::new(1, 1, 1, mock_id);
+                ClientPacket::Login { token: _ } => {
+                    // TODO: Реальная авторизация по токену.
+                    // Токен — секрет: не логируем и не используем как имя.
+                    if my_guid.is_some() {
+                        warn!("Repeated login ignored");
+                        continue;
+                    }
+                    let guid = match state.guids.lock().unwrap_or_else(PoisonError::into_inner).allocate(ObjectKind::Player) {
+                        Ok(guid) => guid,
+                        Err(e) => {
+                            error!("Login rejected: {}", e);
+                            break;
+                        }
+                    };
                     my_guid = Some(guid);
-
                     info!("Client logged in: {:?}", guid);
 
-                    // Уведомляем движок (в реальной системе это тоже InputCmd::Login)
-                    // Но пока мы считаем, что логин прошел
-                    // Отправляем InputCmd::Spawn (если бы он был)
-                    // Для простоты фазы 4: считаем, что движок сам заспавнит по запросу,
-                    // но здесь мы просто запомнили GUID сессии.
+                    // Пока нет профилей — имя из GUID
+                    let name = format!("Player {}", guid.index());
+                    let cmd = InputCmd::Join { entity_guid: guid, name };
+                    if state.cmd_tx.send(cmd).await.is_err() {
+                        error!("Engine is dead");
+                        break;
+                    }
                 }
-                ClientPacket::Move { x, y } => {
+                ClientPacket::Move { x, y, z } => {
                     if let Some(guid) = my_guid {
+                        // new() завернул бы чужие координаты на другой край мира
+                        let target = match WorldPos::try_new(x, y, z) {
+                            Ok(target) => target,
+                            Err(e) => {
+                                warn!("Move from {} rejected: {}", guid, e);
+                                continue;
+                            }
+                        };
                         // Транслируем DTO -> Engine Command
                         let cmd = InputCmd::Move {
                             entity_guid: guid,
-                            target: WorldPos::new(x, y, 0),
+                            target,
                         };
 
                         // Отправляем в движок (Non-blocking)
@@ -121,5 +143,9 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
     }
 
     send_task.abort();
+    if let Some(guid) = my_guid {
+        let _ = state.cmd_tx.send(InputCmd::Leave { entity_guid: guid }).await;
+        state.guids.lock().unwrap_or_else(PoisonError::into_inner).free(guid);
+    }
     info!("Client disconnected {:?}", my_guid);
 }
\ No newline at end of file